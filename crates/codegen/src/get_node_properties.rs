@@ -233,6 +233,9 @@ fn custom_handlers(node: &Node) -> TokenStream {
                 "owner" => {
                     tokens.push(TokenProperty::from(Token::Owner));
                 }
+                "concurrently" => {
+                    tokens.push(TokenProperty::from(Token::Concurrently));
+                }
                 _ => {}
             }
             match n.defaction() {
@@ -487,9 +490,41 @@ fn custom_handlers(node: &Node) -> TokenStream {
         "TransactionStmt" => quote! {
             match n.kind() {
                 protobuf::TransactionStmtKind::TransStmtBegin => tokens.push(TokenProperty::from(Token::BeginP)),
+                protobuf::TransactionStmtKind::TransStmtStart => tokens.push(TokenProperty::from(Token::Start)),
+                // also END
                 protobuf::TransactionStmtKind::TransStmtCommit => tokens.push(TokenProperty::from(Token::Commit)),
+                // also ABORT
+                protobuf::TransactionStmtKind::TransStmtRollback => tokens.push(TokenProperty::from(Token::Rollback)),
+                protobuf::TransactionStmtKind::TransStmtSavepoint => tokens.push(TokenProperty::from(Token::Savepoint)),
+                protobuf::TransactionStmtKind::TransStmtRelease => {
+                    tokens.push(TokenProperty::from(Token::Release));
+                    tokens.push(TokenProperty::from(Token::Savepoint));
+                },
+                protobuf::TransactionStmtKind::TransStmtRollbackTo => {
+                    tokens.push(TokenProperty::from(Token::Rollback));
+                    tokens.push(TokenProperty::from(Token::To));
+                    tokens.push(TokenProperty::from(Token::Savepoint));
+                },
+                protobuf::TransactionStmtKind::TransStmtPrepare => {
+                    tokens.push(TokenProperty::from(Token::Prepare));
+                },
+                protobuf::TransactionStmtKind::TransStmtCommitPrepared => {
+                    tokens.push(TokenProperty::from(Token::Commit));
+                    tokens.push(TokenProperty::from(Token::Prepared));
+                },
+                protobuf::TransactionStmtKind::TransStmtRollbackPrepared => {
+                    tokens.push(TokenProperty::from(Token::Rollback));
+                    tokens.push(TokenProperty::from(Token::Prepared));
+                },
                 _ => panic!("Unknown TransactionStmt {:#?}", n.kind())
             }
+            // optional in BEGIN, COMMIT and ROLLBACK, and required in START and PREPARE
+            tokens.push(TokenProperty::from(Token::Transaction));
+            tokens.push(TokenProperty::from(Token::Work));
+            if n.chain {
+                tokens.push(TokenProperty::from(Token::And));
+                tokens.push(TokenProperty::from(Token::Chain));
+            }
         },
         "PartitionBoundSpec" => quote! {
             tokens.push(TokenProperty::from(Token::From));
@@ -718,6 +753,17 @@ fn custom_handlers(node: &Node) -> TokenStream {
                 tokens.push(TokenProperty::from(Token::Tablespace));
             }
         },
+        "ReindexStmt" => quote! {
+            tokens.push(TokenProperty::from(Token::Reindex));
+            match n.kind() {
+                protobuf::ReindexObjectType::ReindexObjectIndex => tokens.push(TokenProperty::from(Token::Index)),
+                protobuf::ReindexObjectType::ReindexObjectTable => tokens.push(TokenProperty::from(Token::Table)),
+                protobuf::ReindexObjectType::ReindexObjectSchema => tokens.push(TokenProperty::from(Token::Schema)),
+                protobuf::ReindexObjectType::ReindexObjectSystem => tokens.push(TokenProperty::from(Token::SystemP)),
+                protobuf::ReindexObjectType::ReindexObjectDatabase => tokens.push(TokenProperty::from(Token::Database)),
+                _ => panic!("Unknown ReindexStmt {:#?}", n.kind()),
+            }
+        },
         "IndexElem" => quote! {
             if n.collation.len() > 0 {
                 tokens.push(TokenProperty::from(Token::Collate));
@@ -957,6 +1003,47 @@ fn custom_handlers(node: &Node) -> TokenStream {
                 }
             }
         },
+        "DropStmt" => quote! {
+            tokens.push(TokenProperty::from(Token::Drop));
+            match n.remove_type() {
+                protobuf::ObjectType::ObjectTable => tokens.push(TokenProperty::from(Token::Table)),
+                protobuf::ObjectType::ObjectForeignTable => {
+                    tokens.push(TokenProperty::from(Token::Foreign));
+                    tokens.push(TokenProperty::from(Token::Table));
+                },
+                protobuf::ObjectType::ObjectIndex => tokens.push(TokenProperty::from(Token::Index)),
+                protobuf::ObjectType::ObjectView => tokens.push(TokenProperty::from(Token::View)),
+                protobuf::ObjectType::ObjectMatview => {
+                    tokens.push(TokenProperty::from(Token::Materialized));
+                    tokens.push(TokenProperty::from(Token::View));
+                },
+                protobuf::ObjectType::ObjectSequence => tokens.push(TokenProperty::from(Token::Sequence)),
+                protobuf::ObjectType::ObjectSchema => tokens.push(TokenProperty::from(Token::Schema)),
+                protobuf::ObjectType::ObjectType => tokens.push(TokenProperty::from(Token::TypeP)),
+                protobuf::ObjectType::ObjectDomain => tokens.push(TokenProperty::from(Token::DomainP)),
+                protobuf::ObjectType::ObjectExtension => tokens.push(TokenProperty::from(Token::Extension)),
+                protobuf::ObjectType::ObjectFunction => tokens.push(TokenProperty::from(Token::Function)),
+                protobuf::ObjectType::ObjectProcedure => tokens.push(TokenProperty::from(Token::Procedure)),
+                protobuf::ObjectType::ObjectAggregate => tokens.push(TokenProperty::from(Token::Aggregate)),
+                protobuf::ObjectType::ObjectCollation => tokens.push(TokenProperty::from(Token::Collation)),
+                protobuf::ObjectType::ObjectStatisticExt => tokens.push(TokenProperty::from(Token::Statistics)),
+                _ => panic!("Unknown DropStmt {:#?}", n.remove_type()),
+            }
+            if n.concurrent {
+                tokens.push(TokenProperty::from(Token::Concurrently));
+            }
+            if n.missing_ok {
+                tokens.push(TokenProperty::from(Token::IfP));
+                tokens.push(TokenProperty::from(Token::Exists));
+            }
+            match n.behavior {
+                // DropRestrict
+                1 => tokens.push(TokenProperty::from(Token::Restrict)),
+                // DropCascade
+                2 => tokens.push(TokenProperty::from(Token::Cascade)),
+                _ => {}
+            }
+        },
         "TruncateStmt" => quote! {
             tokens.push(TokenProperty::from(Token::Truncate));
             tokens.push(TokenProperty::from(Token::Table));
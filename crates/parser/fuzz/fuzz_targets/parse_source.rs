@@ -0,0 +1,30 @@
+//! Feeds arbitrary text into `parse_source`
+//!
+//! Run with `cargo fuzz run parse_source` from `crates/parser`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use parser::parse_source;
+
+fuzz_target!(|text: &str| {
+    let parse = parse_source(text);
+
+    // the concrete syntax tree must reproduce the source exactly
+    assert_eq!(parse.cst.text().to_string(), text);
+
+    // all ranges must point into the source, at char boundaries
+    let ranges = parse
+        .stmts
+        .iter()
+        .map(|stmt| stmt.range)
+        .chain(parse.errors.iter().map(|error| error.range()));
+    for range in ranges {
+        let (start, end): (usize, usize) = (range.start().into(), range.end().into());
+        assert!(start <= end && end <= text.len(), "{range:?} is out of bounds");
+        assert!(
+            text.is_char_boundary(start) && text.is_char_boundary(end),
+            "{range:?} is not on char boundaries"
+        );
+    }
+});
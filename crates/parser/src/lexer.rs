@@ -117,14 +117,10 @@ pub fn lex(text: &str) -> Vec<Token> {
     while pos < text.len() {
         if !pg_query_tokens.is_empty() && pg_query_tokens[0].start == i32::try_from(pos).unwrap() {
             let pg_query_token = pg_query_tokens.pop_front().unwrap();
-            let token_text: String = text
-                .chars()
-                .skip(usize::try_from(pg_query_token.start).unwrap())
-                .take(
-                    usize::try_from(pg_query_token.end).unwrap()
-                        - usize::try_from(pg_query_token.start).unwrap(),
-                )
-                .collect();
+            // the token positions of pg_query are byte offsets
+            let token_text = text[usize::try_from(pg_query_token.start).unwrap()
+                ..usize::try_from(pg_query_token.end).unwrap()]
+                .to_string();
             let len = token_text.len();
             let has_whitespace = token_text.contains(" ") || token_text.contains("\n");
             tokens.push(Token {
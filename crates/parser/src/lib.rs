@@ -29,6 +29,7 @@ mod syntax_node;
 use lexer::lex;
 use parse::source::source;
 
+pub use crate::ast_node::RawStmt;
 pub use crate::codegen::SyntaxKind;
 pub use crate::parser::{Parse, Parser};
 pub use crate::syntax_node::{SyntaxElement, SyntaxNode, SyntaxToken};
@@ -442,9 +442,18 @@ fn cmp_tokens(p: &crate::codegen::TokenProperty, token: &crate::lexer::Token) ->
     let token_text_values = aliases(&token_text);
 
     (p.value.is_none() || token_text_values.contains(&p.value.as_ref().unwrap().as_str()))
-        && (p.kind.is_none() || p.kind.unwrap() == token.kind)
+        && (p.kind.is_none()
+            || p.kind.unwrap() == token.kind
+            || KEYWORD_SYNONYMS.contains(&(p.kind.unwrap(), token.kind)))
 }
 
+/// keywords that the AST does not distinguish from another keyword, e.g. `END` is parsed as
+/// `COMMIT`
+const KEYWORD_SYNONYMS: [(SyntaxKind, SyntaxKind); 2] = [
+    (SyntaxKind::Commit, SyntaxKind::EndP),
+    (SyntaxKind::Rollback, SyntaxKind::AbortP),
+];
+
 /// returns a list of aliases for a string. primarily used for data types.
 fn aliases(text: &str) -> Vec<&str> {
     for alias in ALIASES {
@@ -18,5 +18,7 @@ pub fn source(parser: &mut Parser) {
         }
     }
 
+    // trailing whitespace is only buffered, so it needs to be applied before closing the tree
+    parser.flush_token_buffer();
     parser.finish_node();
 }
@@ -1,6 +1,6 @@
 use std::ops::Range;
 
-use cstree::text::{TextRange, TextSize};
+use cstree::text::TextRange;
 
 use super::statement_start::{is_at_stmt_start, TokenStatement, STATEMENT_START_TOKEN_MAPS};
 use crate::codegen::SyntaxKind;
@@ -10,6 +10,11 @@ use crate::Parser;
 pub fn statement(parser: &mut Parser, kind: SyntaxKind) {
     let token_range = collect_statement_token_range(parser, kind);
     let tokens = parser.tokens.get(token_range.clone()).unwrap().to_vec();
+    // the byte range of the statement in the source, from its first to its last token
+    let text_range = TextRange::new(
+        tokens.first().unwrap().span.start(),
+        tokens.last().unwrap().span.end(),
+    );
     match pg_query::parse(
         tokens
             .iter()
@@ -27,33 +32,11 @@ pub fn statement(parser: &mut Parser, kind: SyntaxKind) {
                 .0
                 .to_enum();
 
-            // FIXME: if have no idea why the subtraction is needed
-            let start = if parser.tokens[token_range.start].span.start() == TextSize::from(0) {
-                TextSize::from(0)
-            } else {
-                parser.tokens[token_range.start].span.start() - TextSize::from(1)
-            };
-            let end = if token_range.end == parser.tokens.len() {
-                parser.tokens[token_range.end - 1].span.end() - TextSize::from(1)
-            } else {
-                parser.tokens[token_range.end - 1].span.end()
-            };
-            let text_range = TextRange::new(
-                TextSize::from(u32::try_from(start).unwrap()),
-                TextSize::from(u32::try_from(end).unwrap()),
-            );
-
             parser.stmt(root.clone(), text_range);
             libpg_query_node(parser, root, &token_range);
         }
         Err(err) => {
-            parser.error(
-                err.to_string(),
-                TextRange::new(
-                    TextSize::from(u32::try_from(token_range.start).unwrap()),
-                    TextSize::from(u32::try_from(token_range.end).unwrap()),
-                ),
-            );
+            parser.error(err.to_string(), text_range);
             while parser.pos < token_range.end {
                 parser.advance();
             }
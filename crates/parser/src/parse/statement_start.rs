@@ -250,6 +250,97 @@ pub static STATEMENT_START_TOKEN_MAPS: LazyLock<Vec<HashMap<SyntaxKind, Vec<Toke
             &[SyntaxToken::Required(SyntaxKind::Drop)],
         ));
 
+        // DROP followed by an object type that is also the second token of another statement,
+        // e.g. CREATE INDEX
+        m.push((
+            SyntaxKind::DropStmt,
+            &[
+                SyntaxToken::Required(SyntaxKind::Drop),
+                SyntaxToken::Required(SyntaxKind::Table),
+            ],
+        ));
+
+        m.push((
+            SyntaxKind::DropStmt,
+            &[
+                SyntaxToken::Required(SyntaxKind::Drop),
+                SyntaxToken::Required(SyntaxKind::Index),
+            ],
+        ));
+
+        m.push((
+            SyntaxKind::DropStmt,
+            &[
+                SyntaxToken::Required(SyntaxKind::Drop),
+                SyntaxToken::Required(SyntaxKind::View),
+            ],
+        ));
+
+        m.push((
+            SyntaxKind::DropStmt,
+            &[
+                SyntaxToken::Required(SyntaxKind::Drop),
+                SyntaxToken::Required(SyntaxKind::Materialized),
+                SyntaxToken::Required(SyntaxKind::View),
+            ],
+        ));
+
+        m.push((
+            SyntaxKind::DropStmt,
+            &[
+                SyntaxToken::Required(SyntaxKind::Drop),
+                SyntaxToken::Required(SyntaxKind::Sequence),
+            ],
+        ));
+
+        m.push((
+            SyntaxKind::DropStmt,
+            &[
+                SyntaxToken::Required(SyntaxKind::Drop),
+                SyntaxToken::Required(SyntaxKind::Schema),
+            ],
+        ));
+
+        m.push((
+            SyntaxKind::DropStmt,
+            &[
+                SyntaxToken::Required(SyntaxKind::Drop),
+                SyntaxToken::Required(SyntaxKind::TypeP),
+            ],
+        ));
+
+        m.push((
+            SyntaxKind::DropStmt,
+            &[
+                SyntaxToken::Required(SyntaxKind::Drop),
+                SyntaxToken::Required(SyntaxKind::DomainP),
+            ],
+        ));
+
+        m.push((
+            SyntaxKind::DropStmt,
+            &[
+                SyntaxToken::Required(SyntaxKind::Drop),
+                SyntaxToken::Required(SyntaxKind::Extension),
+            ],
+        ));
+
+        m.push((
+            SyntaxKind::DropStmt,
+            &[
+                SyntaxToken::Required(SyntaxKind::Drop),
+                SyntaxToken::Required(SyntaxKind::Function),
+            ],
+        ));
+
+        m.push((
+            SyntaxKind::DropStmt,
+            &[
+                SyntaxToken::Required(SyntaxKind::Drop),
+                SyntaxToken::Required(SyntaxKind::Procedure),
+            ],
+        ));
+
         m.push((
             SyntaxKind::TruncateStmt,
             &[SyntaxToken::Required(SyntaxKind::Truncate)],
@@ -323,15 +414,34 @@ pub static STATEMENT_START_TOKEN_MAPS: LazyLock<Vec<HashMap<SyntaxKind, Vec<Toke
             &[SyntaxToken::Required(SyntaxKind::Unlisten)],
         ));
 
-        // TransactionStmt can be Begin or Commit
+        // TransactionStmt can be BEGIN, START TRANSACTION, COMMIT, END, ROLLBACK or ABORT
         m.push((
             SyntaxKind::TransactionStmt,
             &[SyntaxToken::Required(SyntaxKind::BeginP)],
         ));
+        m.push((
+            SyntaxKind::TransactionStmt,
+            &[
+                SyntaxToken::Required(SyntaxKind::Start),
+                SyntaxToken::Required(SyntaxKind::Transaction),
+            ],
+        ));
         m.push((
             SyntaxKind::TransactionStmt,
             &[SyntaxToken::Required(SyntaxKind::Commit)],
         ));
+        m.push((
+            SyntaxKind::TransactionStmt,
+            &[SyntaxToken::Required(SyntaxKind::EndP)],
+        ));
+        m.push((
+            SyntaxKind::TransactionStmt,
+            &[SyntaxToken::Required(SyntaxKind::Rollback)],
+        ));
+        m.push((
+            SyntaxKind::TransactionStmt,
+            &[SyntaxToken::Required(SyntaxKind::AbortP)],
+        ));
 
         // CREATE [ OR REPLACE ] [ TEMP | TEMPORARY ] [ RECURSIVE ] VIEW
         // this is overly simplified, but it should be good enough for now
@@ -991,9 +1101,9 @@ pub fn is_at_stmt_start(parser: &mut Parser) -> Option<SyntaxKind> {
                     .cloned()
                     .collect();
             }
-        } else if options.len() > 1 {
-            // no result is found, and there is currently more than one option
-            // filter the options for all statements that are complete at this point
+        } else {
+            // no result is found, so filter the options for all statements that are complete at
+            // this point, e.g. START is only a statement start when followed by TRANSACTION
             options.retain(|o| o.is_eos());
         }
 
@@ -57,7 +57,7 @@ pub struct Parse {
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
         Self {
-            eof_token: Token::eof(usize::from(tokens.last().unwrap().span.end())),
+            eof_token: Token::eof(tokens.last().map_or(0, |t| usize::from(t.span.end()))),
             inner: GreenNodeBuilder::new(),
             errors: Vec::new(),
             stmts: Vec::new(),
@@ -137,6 +137,8 @@ impl Parser {
 
     /// Opens a buffer for tokens. While the buffer is active, tokens are not applied to the tree.
     pub fn open_buffer(&mut self) {
+        // whitespace before the buffer belongs to the tree already
+        self.flush_token_buffer();
         self.token_buffer = Some(self.pos);
     }
 
@@ -368,7 +370,9 @@ mod tests {
         let result = p.finish();
 
         dbg!(&result.cst);
-        assert_eq!(result.stmts.len(), 2);
+        // CREATE PROCEDURE is not a statement start, so the body is split into its statements and
+        // the END that closes it
+        assert_eq!(result.stmts.len(), 3);
         println!("{:#?}", result.errors);
     }
 
@@ -391,6 +395,42 @@ mod tests {
         println!("{:#?}", result.errors);
     }
 
+    #[test]
+    fn test_statement_ranges() {
+        init();
+
+        let input = "select 1;\n\n  select 'é';\nselect café";
+
+        let mut p = Parser::new(lex(input));
+        source(&mut p);
+        let result = p.finish();
+
+        let texts = result
+            .stmts
+            .iter()
+            .map(|x| &input[usize::from(x.range.start())..usize::from(x.range.end())])
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["select 1;", "select 'é';", "select café"]);
+    }
+
+    #[test]
+    fn test_syntax_error_range() {
+        init();
+
+        let input = "select 1;\nselect from where;";
+
+        let mut p = Parser::new(lex(input));
+        source(&mut p);
+        let result = p.finish();
+
+        assert_eq!(result.errors.len(), 1);
+        let range = result.errors[0].range();
+        assert_eq!(
+            &input[usize::from(range.start())..usize::from(range.end())],
+            "select from where;"
+        );
+    }
+
     fn panic_after<T, F>(d: Duration, f: F) -> T
     where
         T: Send + 'static,
@@ -0,0 +1,11 @@
+BEGIN;
+BEGIN TRANSACTION;
+START TRANSACTION;
+COMMIT;
+COMMIT WORK;
+END;
+ROLLBACK;
+ABORT;
+ROLLBACK TO SAVEPOINT my_savepoint;
+ROLLBACK TO my_savepoint;
+COMMIT PREPARED 'foobar';
@@ -0,0 +1,1246 @@
+use std::ops;
+
+use cstree::{
+    syntax::{ResolvedNode, ResolvedToken},
+    text::{TextRange, TextSize},
+    util::TokenAtOffset,
+};
+use parser::{RawStmt, SyntaxKind};
+use pg_query::{
+    protobuf::{AlterTableType, KeywordKind, ObjectType, RangeVar, ScanToken, Token},
+    Node, NodeEnum, NodeRef,
+};
+use ropey::Rope;
+use tower_lsp::lsp_types::SymbolKind;
+
+use crate::statement_info::{qualified_name, range_var_name};
+
+/// An object created by a statement in one of the open documents, e.g. a table or a function
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefinedObject {
+    /// The object type as it is written after `CREATE`, e.g. `TABLE` or `FUNCTION`
+    pub kind: &'static str,
+    /// The name of the object, schema-qualified if it is in the source
+    pub name: String,
+    /// Whether the object is temporary and only exists in the session that creates it
+    pub temporary: bool,
+}
+
+impl DefinedObject {
+    pub fn new(stmt: &NodeEnum) -> Option<DefinedObject> {
+        let (kind, name) = match stmt {
+            NodeEnum::CreateStmt(s) => ("TABLE", range_var_name(s.relation.as_ref()?)),
+            NodeEnum::ViewStmt(s) => ("VIEW", range_var_name(s.view.as_ref()?)),
+            NodeEnum::CreateTableAsStmt(s) => {
+                let kind = if s.objtype == ObjectType::ObjectMatview as i32 {
+                    "MATERIALIZED VIEW"
+                } else {
+                    "TABLE"
+                };
+                (kind, range_var_name(s.into.as_ref()?.rel.as_ref()?))
+            }
+            // `SELECT ... INTO new_table` is an older spelling of `CREATE TABLE ... AS`
+            NodeEnum::SelectStmt(s) => (
+                "TABLE",
+                range_var_name(s.into_clause.as_ref()?.rel.as_ref()?),
+            ),
+            NodeEnum::CreateFunctionStmt(s) => {
+                let kind = if s.is_procedure {
+                    "PROCEDURE"
+                } else {
+                    "FUNCTION"
+                };
+                (kind, qualified_name(&s.funcname))
+            }
+            NodeEnum::CreateSeqStmt(s) => ("SEQUENCE", range_var_name(s.sequence.as_ref()?)),
+            NodeEnum::CreateEnumStmt(s) => ("TYPE", qualified_name(&s.type_name)),
+            NodeEnum::CompositeTypeStmt(s) => ("TYPE", range_var_name(s.typevar.as_ref()?)),
+            NodeEnum::CreateDomainStmt(s) => ("DOMAIN", qualified_name(&s.domainname)),
+            NodeEnum::CreateSchemaStmt(s) => ("SCHEMA", s.schemaname.to_owned()),
+            _ => return None,
+        };
+
+        if name.is_empty() {
+            return None;
+        }
+
+        let relation = match stmt {
+            NodeEnum::CreateStmt(s) => s.relation.as_ref(),
+            NodeEnum::ViewStmt(s) => s.view.as_ref(),
+            NodeEnum::CreateTableAsStmt(s) => s.into.as_ref().and_then(|i| i.rel.as_ref()),
+            NodeEnum::SelectStmt(s) => s.into_clause.as_ref().and_then(|i| i.rel.as_ref()),
+            NodeEnum::CreateSeqStmt(s) => s.sequence.as_ref(),
+            _ => None,
+        };
+        let temporary = relation.is_some_and(|r| r.relpersistence == "t");
+
+        Some(DefinedObject {
+            kind,
+            name,
+            temporary,
+        })
+    }
+
+    /// Checks whether `reference`, as written in the source, refers to this object
+    ///
+    /// Unqualified names on either side match any schema.
+    pub fn matches(&self, reference: &str) -> bool {
+        let reference = split_name(reference);
+        let name = self.name.split('.').map(str::to_string).collect::<Vec<_>>();
+        names_match(&reference, &name)
+    }
+
+    pub fn label(&self) -> String {
+        if self.temporary {
+            format!("CREATE TEMPORARY {} {}", self.kind, self.name)
+        } else {
+            format!("CREATE {} {}", self.kind, self.name)
+        }
+    }
+
+    /// Returns the kind of document symbol that represents this object in an outline
+    pub fn symbol_kind(&self) -> SymbolKind {
+        match self.kind {
+            "TABLE" | "MATERIALIZED VIEW" => SymbolKind::STRUCT,
+            "VIEW" => SymbolKind::INTERFACE,
+            "FUNCTION" | "PROCEDURE" => SymbolKind::FUNCTION,
+            "SEQUENCE" => SymbolKind::VARIABLE,
+            "SCHEMA" => SymbolKind::NAMESPACE,
+            _ => SymbolKind::TYPE_PARAMETER,
+        }
+    }
+}
+
+/// Returns the name and kind of the document symbol of a statement with the source `text`
+///
+/// Statements that define an object are labelled after it, all others after their first line.
+pub fn statement_symbol(stmt: &NodeEnum, text: &str) -> Option<(String, SymbolKind)> {
+    match DefinedObject::new(stmt) {
+        Some(object) => Some((object.label(), object.symbol_kind())),
+        None => {
+            let line = text.trim().lines().next()?.trim_end_matches(';');
+            Some((line.to_string(), SymbolKind::OBJECT))
+        }
+    }
+}
+
+/// Returns the name of the relation or function referenced at `offset`, as it is written in the
+/// source
+pub fn reference_at_offset(cst: &ResolvedNode<SyntaxKind>, offset: usize) -> Option<String> {
+    name_at_offset(cst, offset, &[SyntaxKind::RangeVar, SyntaxKind::FuncCall])
+}
+
+/// Returns the name of the relation referenced at `offset`, as it is written in the source
+pub fn relation_at_offset(cst: &ResolvedNode<SyntaxKind>, offset: usize) -> Option<String> {
+    name_at_offset(cst, offset, &[SyntaxKind::RangeVar])
+}
+
+/// Returns the name of the node of one of the `kinds` at `offset`
+fn name_at_offset(
+    cst: &ResolvedNode<SyntaxKind>,
+    offset: usize,
+    kinds: &[SyntaxKind],
+) -> Option<String> {
+    let token = match cst.token_at_offset(TextSize::try_from(offset).ok()?) {
+        TokenAtOffset::Single(token) => token,
+        TokenAtOffset::Between(left, right) => {
+            if right.kind() == SyntaxKind::Ident {
+                right
+            } else {
+                left
+            }
+        }
+        TokenAtOffset::None => return None,
+    };
+
+    if token.kind() != SyntaxKind::Ident {
+        return None;
+    }
+
+    let parent = token.parent();
+    if !kinds.contains(&parent.kind()) {
+        return None;
+    }
+
+    Some(name_tokens(parent).iter().map(|t| t.text()).collect())
+}
+
+/// Returns the ranges of the nodes of one of the `kinds` in `cst` that refer to the same object as
+/// `reference`
+pub fn references_to(
+    cst: &ResolvedNode<SyntaxKind>,
+    reference: &str,
+    kinds: &[SyntaxKind],
+) -> Vec<TextRange> {
+    let reference = split_name(reference);
+    names_matching(cst, kinds, |name| names_match(&reference, name))
+}
+
+/// Returns the ranges of the names of the nodes of one of the `kinds` in `cst` for which `matches`
+/// returns true
+fn names_matching(
+    cst: &ResolvedNode<SyntaxKind>,
+    kinds: &[SyntaxKind],
+    matches: impl Fn(&[String]) -> bool,
+) -> Vec<TextRange> {
+    cst.descendants()
+        .filter(|node| kinds.contains(&node.kind()))
+        .filter_map(|node| {
+            let tokens = name_tokens(node);
+            let name = tokens
+                .iter()
+                .filter(|t| t.kind() == SyntaxKind::Ident)
+                .map(|t| normalize_identifier(t.text()))
+                .collect::<Vec<_>>();
+            if !matches(&name) {
+                return None;
+            }
+            Some(TextRange::new(
+                tokens.first()?.text_range().start(),
+                tokens.last()?.text_range().end(),
+            ))
+        })
+        .collect()
+}
+
+/// Returns the byte ranges of the names in a document that refer to the same relation as
+/// `reference`, i.e. relations, qualifiers of column references and the objects of statements
+/// such as `DROP TABLE`
+///
+/// Unlike `references_to`, the names must be in the same schema as `reference`, see
+/// `same_relation`.
+pub fn relation_names_referring_to(
+    cst: &ResolvedNode<SyntaxKind>,
+    text: &str,
+    reference: &str,
+) -> Vec<ops::Range<usize>> {
+    let reference_parts = split_name(reference);
+    let mut ranges = names_matching(cst, &[SyntaxKind::RangeVar], |name| {
+        same_relation(&reference_parts, name)
+    })
+    .into_iter()
+    .chain(column_qualifiers_referring_to(cst, reference))
+    .map(|r| r.start().into()..r.end().into())
+    .chain(object_names_referring_to(text, reference))
+    .collect::<Vec<_>>();
+    ranges.sort_by_key(|r| r.start);
+    ranges.dedup();
+    ranges
+}
+
+/// Returns the ranges of the qualifiers of column references that refer to the same relation as
+/// `reference`, e.g. `users` in `users.id`
+///
+/// Qualifiers that are the alias of a relation in the same statement, e.g. `users` in
+/// `select users.id from accounts users`, are skipped.
+fn column_qualifiers_referring_to(
+    cst: &ResolvedNode<SyntaxKind>,
+    reference: &str,
+) -> Vec<TextRange> {
+    let reference = split_name(reference);
+
+    cst.descendants()
+        .filter(|node| node.kind() == SyntaxKind::ColumnRef)
+        .filter_map(|node| {
+            let tokens = node
+                .children_with_tokens()
+                .filter_map(|element| element.into_token())
+                .filter(|t| !matches!(t.kind(), SyntaxKind::Whitespace | SyntaxKind::Newline))
+                .collect::<Vec<_>>();
+            // the fields are separated by dots and the last one is the column or `*`
+            let qualifier = &tokens[..tokens.len().checked_sub(2)?];
+            let name = qualifier
+                .iter()
+                .filter(|t| t.kind() != SyntaxKind::Ascii46)
+                .map(|t| normalize_identifier(t.text()))
+                .collect::<Vec<_>>();
+            if !same_relation(&reference, &name) {
+                return None;
+            }
+            if let [qualifier] = name.as_slice() {
+                if statement_aliases(node).contains(qualifier) {
+                    return None;
+                }
+            }
+            Some(TextRange::new(
+                qualifier.first()?.text_range().start(),
+                qualifier.last()?.text_range().end(),
+            ))
+        })
+        .collect()
+}
+
+/// Returns the normalized aliases of the relations in the statement that contains `node`
+fn statement_aliases(node: &ResolvedNode<SyntaxKind>) -> Vec<String> {
+    // the statements are the children of the root
+    let Some(stmt) = node
+        .ancestors()
+        .find(|n| n.parent().is_some_and(|p| p.parent().is_none()))
+    else {
+        return Vec::new();
+    };
+    stmt.descendants()
+        .filter(|n| n.kind() == SyntaxKind::Alias)
+        .filter_map(|alias| {
+            alias
+                .children_with_tokens()
+                .filter_map(|element| element.into_token())
+                .find(|t| t.kind() == SyntaxKind::Ident)
+                .map(|t| normalize_identifier(t.text()))
+        })
+        .collect()
+}
+
+/// Returns the byte ranges of the names in `text` that refer to the same relation as `reference`
+/// and are objects of `DROP`, `COMMENT ON`, `GRANT` or `ALTER` statements
+///
+/// The syntax tree has no nodes for most of these statements, so they are parsed with libpg_query
+/// and their names are looked up in the tokens of the statement.
+fn object_names_referring_to(text: &str, reference: &str) -> Vec<ops::Range<usize>> {
+    let reference = split_name(reference);
+    let (Ok(stmts), Ok(scan)) = (pg_query::split_with_scanner(text), pg_query::scan(text)) else {
+        return Vec::new();
+    };
+
+    let mut ranges = Vec::new();
+    for stmt_text in stmts {
+        let Ok(parsed) = pg_query::parse(stmt_text) else {
+            continue;
+        };
+        // the statements are slices of `text`
+        let stmt_start = stmt_text.as_ptr() as usize - text.as_ptr() as usize;
+        let stmt_end = stmt_start + stmt_text.len();
+        let runs = name_runs(
+            text,
+            scan.tokens
+                .iter()
+                .filter(|t| stmt_start <= t.start as usize && t.end as usize <= stmt_end),
+        );
+
+        for stmt in parsed.protobuf.stmts.iter() {
+            let Some(node) = stmt.stmt.as_ref().and_then(|s| s.node.as_ref()) else {
+                continue;
+            };
+            for name in object_names(node) {
+                let (parts, location) = match name {
+                    ObjectName::Relation(r) => (relation_parts(r), Some(r.location)),
+                    ObjectName::Parts(parts) => (parts, None),
+                };
+                if !same_relation(&reference, &parts) {
+                    continue;
+                }
+                // relations have a location, other names are matched against the tokens
+                ranges.extend(runs.iter().filter_map(|(run_parts, run_ranges)| {
+                    let matches = match location {
+                        Some(location) => run_ranges[0].start == stmt_start + location as usize,
+                        None => run_parts.starts_with(&parts),
+                    };
+                    matches.then(|| run_ranges[0].start..run_ranges[parts.len() - 1].end)
+                }));
+            }
+        }
+    }
+
+    ranges.sort_by_key(|r| r.start);
+    ranges.dedup();
+    ranges
+}
+
+/// A name of an object in a statement, as a relation with its location or as a list of parts
+enum ObjectName<'a> {
+    Relation(&'a RangeVar),
+    Parts(Vec<String>),
+}
+
+/// Returns the names of the relations a `DROP`, `COMMENT ON`, `GRANT` or `ALTER` statement refers
+/// to, or whose column it refers to
+fn object_names(stmt: &NodeEnum) -> Vec<ObjectName<'_>> {
+    let is_relation = |object_type: i32| {
+        matches!(
+            ObjectType::from_i32(object_type),
+            Some(
+                ObjectType::ObjectTable
+                    | ObjectType::ObjectView
+                    | ObjectType::ObjectMatview
+                    | ObjectType::ObjectSequence
+                    | ObjectType::ObjectForeignTable
+            )
+        )
+    };
+    let list_parts = |node: &Node| match &node.node {
+        Some(NodeEnum::List(l)) => Some(
+            l.items
+                .iter()
+                .filter_map(|item| match &item.node {
+                    Some(NodeEnum::String(s)) => Some(s.sval.to_owned()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>(),
+        ),
+        _ => None,
+    };
+
+    match stmt {
+        NodeEnum::DropStmt(s) if is_relation(s.remove_type) => s
+            .objects
+            .iter()
+            .filter_map(list_parts)
+            .map(ObjectName::Parts)
+            .collect(),
+        NodeEnum::CommentStmt(s) => {
+            let Some(mut parts) = s.object.as_deref().and_then(list_parts) else {
+                return Vec::new();
+            };
+            if s.objtype == ObjectType::ObjectColumn as i32 {
+                parts.pop();
+            } else if !is_relation(s.objtype) {
+                return Vec::new();
+            }
+            vec![ObjectName::Parts(parts)]
+        }
+        NodeEnum::GrantStmt(s) if is_relation(s.objtype) => s
+            .objects
+            .iter()
+            .filter_map(|o| match &o.node {
+                Some(NodeEnum::RangeVar(r)) => Some(ObjectName::Relation(r)),
+                _ => None,
+            })
+            .collect(),
+        NodeEnum::AlterTableStmt(s) => s.relation.iter().map(ObjectName::Relation).collect(),
+        NodeEnum::AlterObjectSchemaStmt(s) => s.relation.iter().map(ObjectName::Relation).collect(),
+        NodeEnum::AlterOwnerStmt(s) => s.relation.iter().map(ObjectName::Relation).collect(),
+        NodeEnum::RenameStmt(s) => s.relation.iter().map(ObjectName::Relation).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// A name in the source that refers to a column of a relation
+struct ColumnName {
+    /// The normalized name of the relation, split into its parts
+    relation: Vec<String>,
+    /// The normalized name of the column
+    column: String,
+    range: ops::Range<usize>,
+}
+
+/// Returns the relation and the column whose name is at `offset` in `text`, both as they are
+/// written in SQL
+pub fn column_at_offset(text: &str, offset: usize) -> Option<(String, String)> {
+    let name = column_names(text)
+        .into_iter()
+        .find(|name| name.range.start <= offset && offset <= name.range.end)?;
+    let relation = name
+        .relation
+        .iter()
+        .map(|part| quote_identifier(part))
+        .collect::<Option<Vec<_>>>()?
+        .join(".");
+    Some((relation, quote_identifier(&name.column)?))
+}
+
+/// Returns the byte ranges of the names in `text` that refer to `column` of the same relation as
+/// `relation`, see `same_relation`
+pub fn column_names_referring_to(
+    text: &str,
+    relation: &str,
+    column: &str,
+) -> Vec<ops::Range<usize>> {
+    let relation = split_name(relation);
+    let column = normalize_identifier(column);
+    column_names(text)
+        .into_iter()
+        .filter(|name| name.column == column && same_relation(&relation, &name.relation))
+        .map(|name| name.range)
+        .collect()
+}
+
+/// Returns the names of columns in `text` together with the relation they belong to
+///
+/// The statements are parsed with libpg_query, since the syntax tree does not cover statements
+/// such as `ALTER TABLE`. Unqualified column references are only resolved if their statement
+/// refers to a single relation.
+fn column_names(text: &str) -> Vec<ColumnName> {
+    let (Ok(stmts), Ok(scan)) = (pg_query::split_with_scanner(text), pg_query::scan(text)) else {
+        return Vec::new();
+    };
+
+    let mut names = Vec::new();
+    for stmt_text in stmts {
+        let Ok(parsed) = pg_query::parse(stmt_text) else {
+            continue;
+        };
+        // the statements are slices of `text`
+        let stmt_start = stmt_text.as_ptr() as usize - text.as_ptr() as usize;
+        let stmt_end = stmt_start + stmt_text.len();
+        let runs = name_runs(
+            text,
+            scan.tokens
+                .iter()
+                .filter(|t| stmt_start <= t.start as usize && t.end as usize <= stmt_end),
+        );
+
+        for stmt in parsed.protobuf.stmts.iter() {
+            if let Some(node) = stmt.stmt.as_ref().and_then(|s| s.node.as_ref()) {
+                names.extend(StatementColumns::new(node, &runs, stmt_start).names());
+            }
+        }
+    }
+
+    names.sort_by_key(|name| name.range.start);
+    names.dedup_by_key(|name| name.range.clone());
+    names
+}
+
+/// Finds the column names in a single statement
+struct StatementColumns<'a> {
+    stmt: &'a NodeEnum,
+    runs: &'a [(Vec<String>, Vec<ops::Range<usize>>)],
+    /// The byte offset of the statement, which the locations of its nodes are relative to
+    stmt_start: usize,
+    /// The relations the statement refers to, with their alias
+    relations: Vec<(Vec<String>, Option<String>)>,
+}
+
+impl<'a> StatementColumns<'a> {
+    fn new(
+        stmt: &'a NodeEnum,
+        runs: &'a [(Vec<String>, Vec<ops::Range<usize>>)],
+        stmt_start: usize,
+    ) -> Self {
+        let relations = stmt
+            .nodes()
+            .into_iter()
+            .filter_map(|(node, _, _)| match node {
+                NodeRef::RangeVar(r) => Some((
+                    relation_parts(r),
+                    r.alias.as_ref().map(|a| a.aliasname.to_owned()),
+                )),
+                _ => None,
+            })
+            .collect();
+        StatementColumns {
+            stmt,
+            runs,
+            stmt_start,
+            relations,
+        }
+    }
+
+    fn names(&self) -> Vec<ColumnName> {
+        let target = match self.stmt {
+            NodeEnum::CreateStmt(s) => s.relation.as_ref(),
+            NodeEnum::AlterTableStmt(s) => s.relation.as_ref(),
+            NodeEnum::IndexStmt(s) => s.relation.as_ref(),
+            NodeEnum::InsertStmt(s) => s.relation.as_ref(),
+            NodeEnum::UpdateStmt(s) => s.relation.as_ref(),
+            NodeEnum::RenameStmt(s) if s.rename_type == ObjectType::ObjectColumn as i32 => {
+                s.relation.as_ref()
+            }
+            _ => None,
+        };
+
+        let mut names = self
+            .stmt
+            .nodes()
+            .into_iter()
+            .filter_map(|(node, _, _)| match node {
+                NodeRef::ColumnRef(c) => self.column_ref(&c.fields, c.location),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        // `NodeEnum::nodes` does not visit the columns and constraints of tables
+        let elements = match self.stmt {
+            NodeEnum::CreateStmt(s) => s.table_elts.iter().collect(),
+            NodeEnum::AlterTableStmt(s) => s
+                .cmds
+                .iter()
+                .filter_map(|cmd| match &cmd.node {
+                    Some(NodeEnum::AlterTableCmd(cmd)) => cmd.def.as_deref(),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let mut constraints = Vec::new();
+        for element in elements {
+            match &element.node {
+                Some(NodeEnum::ColumnDef(d)) => {
+                    names.extend(target.and_then(|t| self.name_at(t, &d.colname, d.location)));
+                    constraints.extend(d.constraints.iter().filter_map(|c| match &c.node {
+                        Some(NodeEnum::Constraint(c)) => Some(c),
+                        _ => None,
+                    }));
+                }
+                Some(NodeEnum::Constraint(c)) => constraints.push(c),
+                _ => {}
+            }
+        }
+        for c in constraints {
+            let mut from = self.stmt_start + c.location as usize;
+            for key in c.keys.iter().chain(c.fk_attrs.iter()) {
+                names.extend(target.and_then(|t| self.name_after(t, key, &mut from)));
+            }
+            if let Some(pktable) = &c.pktable {
+                let mut from = self.stmt_start + pktable.location as usize;
+                for key in c.pk_attrs.iter() {
+                    names.extend(self.name_after(pktable, key, &mut from));
+                }
+            }
+        }
+
+        if let NodeEnum::CommentStmt(s) = self.stmt {
+            if s.objtype == ObjectType::ObjectColumn as i32 {
+                names.extend(s.object.as_deref().and_then(|o| self.commented_column(o)));
+            }
+        }
+
+        let Some(target) = target else {
+            return names;
+        };
+        // the names without a location follow the relation
+        let mut from = self.stmt_start + target.location as usize;
+        let mut search = |name: &str| {
+            let range = self.find_run(&[name.to_string()], from)?;
+            from = range.end;
+            Some(ColumnName {
+                relation: relation_parts(target),
+                column: name.to_string(),
+                range,
+            })
+        };
+        match self.stmt {
+            NodeEnum::InsertStmt(s) => names.extend(s.cols.iter().filter_map(|c| match &c.node {
+                Some(NodeEnum::ResTarget(t)) => self.name_at(target, &t.name, t.location),
+                _ => None,
+            })),
+            NodeEnum::UpdateStmt(s) => {
+                names.extend(s.target_list.iter().filter_map(|c| match &c.node {
+                    Some(NodeEnum::ResTarget(t)) => self.name_at(target, &t.name, t.location),
+                    _ => None,
+                }))
+            }
+            NodeEnum::IndexStmt(s) => {
+                names.extend(s.index_params.iter().filter_map(|p| match &p.node {
+                    Some(NodeEnum::IndexElem(e)) if !e.name.is_empty() => search(&e.name),
+                    _ => None,
+                }))
+            }
+            NodeEnum::AlterTableStmt(s) => {
+                names.extend(s.cmds.iter().filter_map(|cmd| match &cmd.node {
+                    Some(NodeEnum::AlterTableCmd(cmd)) if is_column_command(cmd.subtype) => {
+                        search(&cmd.name)
+                    }
+                    _ => None,
+                }))
+            }
+            NodeEnum::RenameStmt(s) => names.extend(search(&s.subname)),
+            _ => {}
+        }
+        names
+    }
+
+    /// Resolves a column reference such as `u.id` to the relation it belongs to
+    fn column_ref(&self, fields: &[Node], location: i32) -> Option<ColumnName> {
+        let parts = fields
+            .iter()
+            .map(|f| match &f.node {
+                Some(NodeEnum::String(s)) => Some(s.sval.to_owned()),
+                // `*`
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let (column, qualifier) = parts.split_last()?;
+
+        let relation = match qualifier {
+            [] => {
+                let mut relations = self.relations.iter().map(|(r, _)| r).collect::<Vec<_>>();
+                relations.dedup();
+                match relations.as_slice() {
+                    [relation] => relation.to_vec(),
+                    _ => return None,
+                }
+            }
+            [alias] => self
+                .relations
+                .iter()
+                .find(|(_, a)| a.as_ref() == Some(alias))
+                .map_or_else(|| qualifier.to_vec(), |(r, _)| r.to_vec()),
+            _ => qualifier.to_vec(),
+        };
+
+        let (_, ranges) = self
+            .runs
+            .iter()
+            .find(|(_, ranges)| ranges[0].start == self.stmt_start + location as usize)?;
+        Some(ColumnName {
+            relation,
+            column: column.to_owned(),
+            range: ranges.get(parts.len() - 1)?.clone(),
+        })
+    }
+
+    /// Returns the column of `COMMENT ON COLUMN`, whose object is a list of the names of the
+    /// relation and the column
+    fn commented_column(&self, object: &Node) -> Option<ColumnName> {
+        let Some(NodeEnum::List(list)) = &object.node else {
+            return None;
+        };
+        let parts = list
+            .items
+            .iter()
+            .map(|item| match &item.node {
+                Some(NodeEnum::String(s)) => Some(s.sval.to_owned()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let (column, relation) = parts.split_last()?;
+        let (_, ranges) = self.runs.iter().find(|(run_parts, ranges)| {
+            ranges[0].start >= self.stmt_start && run_parts == &parts
+        })?;
+        Some(ColumnName {
+            relation: relation.to_vec(),
+            column: column.to_owned(),
+            range: ranges.last()?.clone(),
+        })
+    }
+
+    /// Returns the name of a column of `relation` that starts at `location`
+    fn name_at(&self, relation: &RangeVar, name: &str, location: i32) -> Option<ColumnName> {
+        let (_, ranges) = self
+            .runs
+            .iter()
+            .find(|(_, ranges)| ranges[0].start == self.stmt_start + location as usize)?;
+        Some(ColumnName {
+            relation: relation_parts(relation),
+            column: name.to_string(),
+            range: ranges[0].clone(),
+        })
+    }
+
+    /// Returns the first name of a column of `relation` in a list of names, e.g. the keys of a
+    /// constraint, after the byte offset `from`, which is moved past it
+    fn name_after(&self, relation: &RangeVar, key: &Node, from: &mut usize) -> Option<ColumnName> {
+        let Some(NodeEnum::String(name)) = &key.node else {
+            return None;
+        };
+        let range = self.find_run(&[name.sval.to_owned()], *from)?;
+        *from = range.end;
+        Some(ColumnName {
+            relation: relation_parts(relation),
+            column: name.sval.to_owned(),
+            range,
+        })
+    }
+
+    /// Returns the range of the first run of names that equals `parts` after the byte offset
+    /// `from`
+    fn find_run(&self, parts: &[String], from: usize) -> Option<ops::Range<usize>> {
+        self.runs
+            .iter()
+            .find(|(run_parts, ranges)| ranges[0].start >= from && run_parts == parts)
+            .map(|(_, ranges)| ranges[0].clone())
+    }
+}
+
+/// Checks whether an `ALTER TABLE` command refers to a column by its name
+fn is_column_command(subtype: i32) -> bool {
+    matches!(
+        AlterTableType::from_i32(subtype),
+        Some(
+            AlterTableType::AtColumnDefault
+                | AlterTableType::AtDropNotNull
+                | AlterTableType::AtSetNotNull
+                | AlterTableType::AtDropExpression
+                | AlterTableType::AtSetStatistics
+                | AlterTableType::AtSetOptions
+                | AlterTableType::AtResetOptions
+                | AlterTableType::AtSetStorage
+                | AlterTableType::AtSetCompression
+                | AlterTableType::AtDropColumn
+                | AlterTableType::AtAlterColumnType
+                | AlterTableType::AtAddIdentity
+                | AlterTableType::AtSetIdentity
+                | AlterTableType::AtDropIdentity
+        )
+    )
+}
+
+fn relation_parts(relation: &RangeVar) -> Vec<String> {
+    [&relation.schemaname, &relation.relname]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .cloned()
+        .collect()
+}
+
+/// Returns the runs of names separated by dots in `tokens`, e.g. `app.users`, as their normalized
+/// parts and the byte range of each part
+fn name_runs<'a>(
+    text: &str,
+    tokens: impl Iterator<Item = &'a ScanToken>,
+) -> Vec<(Vec<String>, Vec<ops::Range<usize>>)> {
+    let mut runs: Vec<(Vec<String>, Vec<ops::Range<usize>>)> = Vec::new();
+    let mut after_dot = false;
+    for token in tokens {
+        let range = token.start as usize..token.end as usize;
+        if token.token() == Token::Ascii46 {
+            after_dot = true;
+            continue;
+        }
+        let is_name =
+            token.token() == Token::Ident || token.keyword_kind() != KeywordKind::NoKeyword;
+        if !is_name {
+            after_dot = false;
+            continue;
+        }
+        let part = normalize_identifier(&text[range.clone()]);
+        match runs.last_mut() {
+            Some((parts, ranges)) if after_dot => {
+                parts.push(part);
+                ranges.push(range);
+            }
+            _ => runs.push((vec![part], vec![range])),
+        }
+        after_dot = false;
+    }
+    runs
+}
+
+/// Returns `name` as it is written in SQL, quoted if it is not a plain lower case name or is a
+/// keyword, or `None` if it is not a valid identifier
+///
+/// Names that are already quoted are returned as they are.
+pub fn quote_identifier(name: &str) -> Option<String> {
+    if name.starts_with('"') {
+        let scan = pg_query::scan(name).ok()?;
+        return match scan.tokens.as_slice() {
+            [token] if token.token() == Token::Ident && token.end as usize == name.len() => {
+                Some(name.to_string())
+            }
+            _ => None,
+        };
+    }
+
+    if name.is_empty() {
+        return None;
+    }
+    let is_plain = name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '$');
+    // unreserved keywords such as `name` can be used as identifiers
+    let is_keyword = pg_query::scan(name).map_or(true, |scan| {
+        scan.tokens.iter().any(|t| {
+            !matches!(
+                t.keyword_kind(),
+                KeywordKind::NoKeyword | KeywordKind::UnreservedKeyword
+            )
+        })
+    });
+    if is_plain && !is_keyword {
+        Some(name.to_string())
+    } else {
+        Some(format!("\"{}\"", name.replace('"', "\"\"")))
+    }
+}
+
+/// Returns the leading identifiers of a node with the dots between them, which make up its name,
+/// e.g. `public.contact` in `public.contact c`
+fn name_tokens(node: &ResolvedNode<SyntaxKind>) -> Vec<&ResolvedToken<SyntaxKind>> {
+    node.children_with_tokens()
+        .map_while(|element| {
+            element
+                .into_token()
+                .filter(|t| matches!(t.kind(), SyntaxKind::Ident | SyntaxKind::Ascii46))
+        })
+        .collect()
+}
+
+/// Splits a name as it is written in the source into its normalized parts
+fn split_name(name: &str) -> Vec<String> {
+    name.split('.').map(normalize_identifier).collect()
+}
+
+/// Checks whether two names, split into their parts, refer to the same object
+fn names_match(a: &[String], b: &[String]) -> bool {
+    match (a, b) {
+        ([.., a_schema, a_name], [.., b_schema, b_name]) => {
+            a_schema == b_schema && a_name == b_name
+        }
+        ([.., a_name], [.., b_name]) => a_name == b_name,
+        _ => false,
+    }
+}
+
+/// Checks whether two names, split into their parts, refer to the same relation
+///
+/// Unlike `names_match`, a qualified name only matches names in the same schema. Unqualified names
+/// are assumed to be in `public`, the first schema of the default search path.
+fn same_relation(a: &[String], b: &[String]) -> bool {
+    let schema = |name: &[String]| match name {
+        [.., schema, _] => schema.to_owned(),
+        _ => "public".to_string(),
+    };
+    a.last().is_some() && a.last() == b.last() && schema(a) == schema(b)
+}
+
+/// Returns the block of `--` comments directly above the statement, without the comment markers
+pub fn leading_comment(stmt: &RawStmt, rope: &Rope) -> Option<String> {
+    // statement ranges start at their first token, but a range that starts early, e.g. at the
+    // line break before the statement, must not make the line of the statement the one above it
+    let mut char_idx = rope.try_byte_to_char(stmt.range.start().into()).ok()?;
+    while rope.get_char(char_idx).is_some_and(|c| c.is_whitespace()) {
+        char_idx += 1;
+    }
+
+    let mut line_idx = rope.try_char_to_line(char_idx).ok()?;
+    let mut lines = Vec::new();
+    while line_idx > 0 {
+        line_idx -= 1;
+        let line = rope.line(line_idx).to_string();
+        match line.trim().strip_prefix("--") {
+            Some(comment) => lines.push(comment.trim().to_string()),
+            None => break,
+        }
+    }
+
+    if lines.is_empty() {
+        return None;
+    }
+
+    lines.reverse();
+    Some(lines.join("\n"))
+}
+
+/// Folds unquoted identifiers to lower case and strips the quotes from quoted ones, like Postgres
+/// does
+fn normalize_identifier(ident: &str) -> String {
+    match ident.strip_prefix('"').and_then(|i| i.strip_suffix('"')) {
+        Some(quoted) => quoted.to_string(),
+        None => ident.to_lowercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cstree::text::{TextRange, TextSize};
+    use parser::parse_source;
+    use ropey::Rope;
+    use tower_lsp::lsp_types::SymbolKind;
+
+    use super::{
+        column_at_offset, column_names_referring_to, leading_comment, object_names_referring_to,
+        quote_identifier, relation_names_referring_to, statement_symbol, DefinedObject,
+    };
+    use crate::test_utils::parse;
+
+    /// Renames the relation `reference` in `text` like the rename request does
+    fn rename(text: &str, reference: &str, new_name: &str) -> String {
+        let parse = parse_source(text);
+        let mut renamed = text.to_string();
+        for range in relation_names_referring_to(&parse.cst, text, reference)
+            .into_iter()
+            .rev()
+        {
+            let new_text = match text[range.clone()].rsplit_once('.') {
+                Some((schema, _)) => format!("{}.{}", schema, new_name),
+                None => new_name.to_string(),
+            };
+            renamed.replace_range(range, &new_text);
+        }
+        renamed
+    }
+
+    #[test]
+    fn defined_objects() {
+        let cases = [
+            (
+                "create table app.users (id int)",
+                Some(("TABLE", "app.users", false)),
+            ),
+            (
+                "create temp table users (id int)",
+                Some(("TABLE", "users", true)),
+            ),
+            ("create view v as select 1", Some(("VIEW", "v", false))),
+            (
+                "create temporary view v as select 1",
+                Some(("VIEW", "v", true)),
+            ),
+            (
+                "create materialized view m as select 1",
+                Some(("MATERIALIZED VIEW", "m", false)),
+            ),
+            ("create table c as select 1", Some(("TABLE", "c", false))),
+            ("select * into c from t", Some(("TABLE", "c", false))),
+            ("select * into temp c from t", Some(("TABLE", "c", true))),
+            (
+                "create function f() returns int as 'select 1' language sql",
+                Some(("FUNCTION", "f", false)),
+            ),
+            (
+                "create procedure app.p() as 'select 1' language sql",
+                Some(("PROCEDURE", "app.p", false)),
+            ),
+            ("create temp sequence s", Some(("SEQUENCE", "s", true))),
+            (
+                "create type mood as enum ('happy')",
+                Some(("TYPE", "mood", false)),
+            ),
+            (
+                "create type pair as (a int, b int)",
+                Some(("TYPE", "pair", false)),
+            ),
+            (
+                "create domain email as text",
+                Some(("DOMAIN", "email", false)),
+            ),
+            ("create schema app", Some(("SCHEMA", "app", false))),
+            ("select * from t", None),
+            ("drop table t", None),
+        ];
+
+        for (sql, expected) in cases {
+            let object = DefinedObject::new(&parse(sql));
+            assert_eq!(
+                object
+                    .as_ref()
+                    .map(|o| (o.kind, o.name.as_str(), o.temporary)),
+                expected,
+                "{}",
+                sql
+            );
+        }
+    }
+
+    #[test]
+    fn rename_relation() {
+        let text = "create table users (id int);\n\
+                    select users.id, o.total from users join app.orders o on o.user_id = users.id;\n\
+                    select public.users.id from public.users;\n\
+                    select users.id from accounts users;\n\
+                    select orders.id from orders;\n\
+                    drop table users, app.orders;\n";
+        assert_eq!(
+            rename(text, "users", "members"),
+            "create table members (id int);\n\
+             select members.id, o.total from members join app.orders o on o.user_id = members.id;\n\
+             select public.members.id from public.members;\n\
+             select users.id from accounts users;\n\
+             select orders.id from orders;\n\
+             drop table members, app.orders;\n"
+        );
+        // `orders` is `public.orders`, which is another table
+        assert_eq!(
+            rename(text, "app.orders", "purchases"),
+            "create table users (id int);\n\
+             select users.id, o.total from users join app.purchases o on o.user_id = users.id;\n\
+             select public.users.id from public.users;\n\
+             select users.id from accounts users;\n\
+             select orders.id from orders;\n\
+             drop table users, app.purchases;\n"
+        );
+    }
+
+    #[test]
+    fn rename_column() {
+        let text = "create table users (id int primary key, email text);\n\
+                    create table invites (email text references users (email));\n\
+                    create index users_email on users (email);\n\
+                    select id, users.email from users where email like '%email%';\n\
+                    select u.email from app.users u;\n\
+                    select email from invites;\n\
+                    insert into users (id, email) values (1, null);\n\
+                    alter table users alter column email set not null, drop column id;\n\
+                    alter table users rename column email to mail;\n\
+                    comment on column users.email is 'email';\n";
+        let mut renamed = text.to_string();
+        for range in column_names_referring_to(text, "users", "email")
+            .into_iter()
+            .rev()
+        {
+            renamed.replace_range(range, "address");
+        }
+        assert_eq!(
+            renamed,
+            "create table users (id int primary key, address text);\n\
+             create table invites (email text references users (address));\n\
+             create index users_email on users (address);\n\
+             select id, users.address from users where address like '%email%';\n\
+             select u.email from app.users u;\n\
+             select email from invites;\n\
+             insert into users (id, address) values (1, null);\n\
+             alter table users alter column address set not null, drop column id;\n\
+             alter table users rename column address to mail;\n\
+             comment on column users.address is 'email';\n"
+        );
+    }
+
+    #[test]
+    fn columns_at_offset() {
+        let text = "select u.email, id from app.users u;\nselect \"Id\" from users;";
+        let cases = [
+            (9, Some(("app.users", "email"))),
+            (16, Some(("app.users", "id"))),
+            (20, None),
+            (45, Some(("users", "\"Id\""))),
+        ];
+
+        for (offset, expected) in cases {
+            let column = column_at_offset(text, offset);
+            assert_eq!(
+                column.as_ref().map(|(r, c)| (r.as_str(), c.as_str())),
+                expected,
+                "{}",
+                offset
+            );
+        }
+    }
+
+    #[test]
+    fn object_names() {
+        let cases: [(&str, &[&str]); 11] = [
+            ("drop table users cascade", &["users"]),
+            ("drop view public.users", &["public.users"]),
+            // another schema's table of the same name
+            ("drop view app.users", &[]),
+            ("drop function users()", &[]),
+            ("comment on table users is 'users'", &["users"]),
+            (
+                "comment on column public.users.id is 'users.id'",
+                &["public.users"],
+            ),
+            ("comment on function users() is 'users'", &[]),
+            ("grant select on users, \"Users\" to users", &["users"]),
+            ("alter view users owner to admin", &["users"]),
+            ("alter table users set schema app", &["users"]),
+            ("alter table if exists users rename to members", &["users"]),
+        ];
+
+        for (text, expected) in cases {
+            let names = object_names_referring_to(text, "users")
+                .into_iter()
+                .map(|range| &text[range])
+                .collect::<Vec<_>>();
+            assert_eq!(names, expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn statement_symbols() {
+        let cases = [
+            (
+                "create table app.users (id int);",
+                "CREATE TABLE app.users",
+                SymbolKind::STRUCT,
+            ),
+            (
+                "create temp view v as select 1",
+                "CREATE TEMPORARY VIEW v",
+                SymbolKind::INTERFACE,
+            ),
+            (
+                "create materialized view m as select 1",
+                "CREATE MATERIALIZED VIEW m",
+                SymbolKind::STRUCT,
+            ),
+            (
+                "create function f() returns int as 'select 1' language sql",
+                "CREATE FUNCTION f",
+                SymbolKind::FUNCTION,
+            ),
+            (
+                "create sequence s",
+                "CREATE SEQUENCE s",
+                SymbolKind::VARIABLE,
+            ),
+            (
+                "create schema app",
+                "CREATE SCHEMA app",
+                SymbolKind::NAMESPACE,
+            ),
+            (
+                "create domain email as text",
+                "CREATE DOMAIN email",
+                SymbolKind::TYPE_PARAMETER,
+            ),
+            ("select 1;", "select 1", SymbolKind::OBJECT),
+            (
+                "\n  insert into users\n  values (1);",
+                "insert into users",
+                SymbolKind::OBJECT,
+            ),
+        ];
+
+        for (text, label, kind) in cases {
+            assert_eq!(
+                statement_symbol(&parse(text), text),
+                Some((label.to_string(), kind)),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn leading_comments() {
+        let cases = [
+            (
+                "select 1;\n-- the users\n--   of the app\ncreate table users (id int);",
+                Some("the users\nof the app"),
+            ),
+            (
+                "-- unrelated\n\n-- the users\ncreate table users (id int);",
+                Some("the users"),
+            ),
+            ("-- unrelated\n\ncreate table users (id int);", None),
+            ("select 1; -- the one\ncreate table users (id int);", None),
+            ("create table users (id int); -- the users", None),
+        ];
+
+        for (text, expected) in cases {
+            let parse = parse_source(text);
+            let stmt = parse.stmts.last().unwrap();
+            assert_eq!(
+                leading_comment(stmt, &Rope::from_str(text)).as_deref(),
+                expected,
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn leading_comment_of_range_starting_early() {
+        let text = "-- the users\ncreate table users (id int);";
+        let mut stmt = parse_source(text).stmts.remove(0);
+        // the range starts at the line break before the statement
+        stmt.range = TextRange::new(TextSize::from(12), stmt.range.end());
+        assert_eq!(
+            leading_comment(&stmt, &Rope::from_str(text)).as_deref(),
+            Some("the users")
+        );
+    }
+
+    #[test]
+    fn quoted_identifiers() {
+        let cases = [
+            ("members", Some("members")),
+            ("user_2", Some("user_2")),
+            ("name", Some("name")),
+            ("Members", Some("\"Members\"")),
+            ("team members", Some("\"team members\"")),
+            ("select", Some("\"select\"")),
+            ("say \"hi\"", Some("\"say \"\"hi\"\"\"")),
+            ("\"Members\"", Some("\"Members\"")),
+            ("\"unterminated", None),
+            ("\"a\".\"b\"", None),
+            ("", None),
+        ];
+
+        for (name, expected) in cases {
+            assert_eq!(quote_identifier(name).as_deref(), expected, "{}", name);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use pg_query::{
+    protobuf::{Constraint, ScanToken},
+    NodeEnum,
+};
+
+use crate::locks::foreign_keys;
+use crate::statement_info::range_var_name;
+
+/// Returns the `ON DELETE` action of a foreign key as it is written in SQL
+fn on_delete_action(constraint: &Constraint) -> &'static str {
+    match constraint.fk_del_action.as_str() {
+        "r" => "RESTRICT",
+        "c" => "CASCADE",
+        "n" => "SET NULL",
+        "d" => "SET DEFAULT",
+        _ => "NO ACTION",
+    }
+}
+
+/// Checks whether a foreign key has an `ON DELETE` clause
+///
+/// Postgres records an explicit `ON DELETE NO ACTION` the same way as a missing clause, so the
+/// tokens of the constraint in `stmt_text` are searched instead.
+fn has_on_delete_clause(stmt_text: &str, constraint: &Constraint) -> bool {
+    let Ok(scan) = pg_query::scan(stmt_text) else {
+        return false;
+    };
+    let token_text =
+        |t: &ScanToken| stmt_text[t.start as usize..t.end as usize].to_ascii_lowercase();
+
+    let mut depth = 0;
+    let mut previous = String::new();
+    for t in scan
+        .tokens
+        .iter()
+        .filter(|t| t.start >= constraint.location)
+    {
+        let text = token_text(t);
+        match text.as_str() {
+            // the constraint ends with the table element or command it belongs to
+            "," | ")" | ";" if depth == 0 => break,
+            "(" => depth += 1,
+            ")" => depth -= 1,
+            "delete" if previous == "on" => return true,
+            _ => {}
+        }
+        previous = text;
+    }
+    false
+}
+
+/// Returns the problems with the `ON DELETE` actions of the foreign keys a statement adds
+///
+/// `stmt_text` is the source of the statement, which the locations of its nodes refer to.
+pub fn on_delete_problems(
+    stmt: &NodeEnum,
+    stmt_text: &str,
+    require_on_delete: bool,
+    disallowed_actions: &[String],
+) -> Vec<String> {
+    foreign_keys(stmt)
+        .into_iter()
+        .filter_map(|constraint| {
+            let target = constraint
+                .pktable
+                .as_ref()
+                .map_or_else(String::new, range_var_name);
+            let action = on_delete_action(constraint);
+            if disallowed_actions
+                .iter()
+                .any(|disallowed| disallowed.eq_ignore_ascii_case(action))
+            {
+                Some(format!(
+                    "Foreign key to {} uses ON DELETE {}, which is not allowed",
+                    target, action
+                ))
+            } else if require_on_delete && !has_on_delete_clause(stmt_text, constraint) {
+                Some(format!(
+                    "Foreign key to {} does not specify an ON DELETE action",
+                    target
+                ))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::on_delete_problems;
+    use crate::test_utils::parse;
+
+    #[test]
+    fn on_delete_actions() {
+        let disallowed = ["cascade".to_string()];
+        let cases: [(&str, &[&str]); 9] = [
+            ("create table t (id int)", &[]),
+            (
+                "create table t (user_id int references users)",
+                &["Foreign key to users does not specify an ON DELETE action"],
+            ),
+            (
+                "create table t (user_id int references users on delete no action)",
+                &[],
+            ),
+            (
+                "create table t (user_id int references users on delete restrict)",
+                &[],
+            ),
+            (
+                "create table t (user_id int references users on update cascade)",
+                &["Foreign key to users does not specify an ON DELETE action"],
+            ),
+            (
+                "create table t (user_id int references app.users on delete cascade)",
+                &["Foreign key to app.users uses ON DELETE CASCADE, which is not allowed"],
+            ),
+            // the clause of one foreign key does not count for another
+            (
+                "create table t (a int references a on delete set null, b int references b)",
+                &["Foreign key to b does not specify an ON DELETE action"],
+            ),
+            (
+                "create table t (a int, b int, foreign key (a, b) references ab (x, y))",
+                &["Foreign key to ab does not specify an ON DELETE action"],
+            ),
+            (
+                "alter table t add constraint fk foreign key (a) references a on delete no action",
+                &[],
+            ),
+        ];
+
+        for (sql, expected) in cases {
+            assert_eq!(
+                on_delete_problems(&parse(sql), sql, true, &disallowed),
+                expected,
+                "{}",
+                sql
+            );
+        }
+    }
+
+    #[test]
+    fn on_delete_is_optional_by_default() {
+        let sql = "create table t (user_id int references users)";
+        assert!(on_delete_problems(&parse(sql), sql, false, &[]).is_empty());
+    }
+}
@@ -0,0 +1,183 @@
+use std::ops::Range;
+
+use pg_query::protobuf::{KeywordKind, ScanToken};
+use serde::Deserialize;
+
+/// The case the formatter writes keywords in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeywordCase {
+    #[default]
+    Upper,
+    Lower,
+    /// Leaves keywords as they are written
+    Preserve,
+}
+
+/// Returns the byte ranges of reserved keywords that are not in the given case, together with
+/// their replacement
+///
+/// Only reserved keywords are changed since unreserved ones such as `name` or `type` are commonly
+/// used as identifiers. Reserved keywords used as column labels, i.e. after `AS` in a target list
+/// or after a `.`, are left alone as well. Since unquoted identifiers and keywords are case
+/// insensitive, the edits never change the meaning of the source.
+pub fn keyword_case_edits(text: &str, case: KeywordCase) -> Vec<(Range<usize>, String)> {
+    if case == KeywordCase::Preserve {
+        return Vec::new();
+    }
+    let Ok(scan) = pg_query::scan(text) else {
+        return Vec::new();
+    };
+    let token_text = |t: &ScanToken| &text[t.start as usize..t.end as usize];
+
+    let labels = column_labels(&scan.tokens, token_text);
+
+    scan.tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.keyword_kind() == KeywordKind::ReservedKeyword)
+        .filter(|(idx, _)| {
+            let previous = idx.checked_sub(1).map(|i| token_text(&scan.tokens[i]));
+            previous != Some(".") && !labels[*idx]
+        })
+        .filter_map(|(_, t)| {
+            let keyword = token_text(t);
+            let replacement = match case {
+                KeywordCase::Lower => keyword.to_ascii_lowercase(),
+                _ => keyword.to_ascii_uppercase(),
+            };
+            (keyword != replacement).then_some((t.start as usize..t.end as usize, replacement))
+        })
+        .collect()
+}
+
+/// Returns for each token whether it is a column label after `AS` in the target list of a
+/// `SELECT` or a `RETURNING` clause
+///
+/// Elsewhere, e.g. in `CREATE VIEW v AS SELECT`, the token after `AS` is a keyword.
+fn column_labels<'a>(
+    tokens: &[ScanToken],
+    token_text: impl Fn(&ScanToken) -> &'a str,
+) -> Vec<bool> {
+    // whether the parser is in a target list, per level of parentheses
+    let mut in_target_list = vec![false];
+    let mut labels = vec![false; tokens.len()];
+    for (idx, t) in tokens.iter().enumerate() {
+        let text = token_text(t).to_ascii_lowercase();
+        let previous = idx.checked_sub(1).map(|i| token_text(&tokens[i]));
+        if *in_target_list.last().unwrap()
+            && matches!(previous, Some(p) if p.eq_ignore_ascii_case("as"))
+        {
+            labels[idx] = true;
+            continue;
+        }
+        if previous == Some(".") {
+            // a qualified name, e.g. `t.from`
+            continue;
+        }
+        match text.as_str() {
+            "(" => in_target_list.push(false),
+            ")" if in_target_list.len() > 1 => {
+                in_target_list.pop();
+            }
+            ";" => in_target_list = vec![false],
+            "select" | "returning" => *in_target_list.last_mut().unwrap() = true,
+            "from" | "into" | "where" | "group" | "having" | "window" | "order" | "limit"
+            | "offset" | "fetch" | "for" | "union" | "intersect" | "except" => {
+                *in_target_list.last_mut().unwrap() = false
+            }
+            _ => {}
+        }
+    }
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{keyword_case_edits, KeywordCase};
+
+    fn format(text: &str, case: KeywordCase) -> String {
+        let mut formatted = text.to_string();
+        for (range, replacement) in keyword_case_edits(text, case).into_iter().rev() {
+            formatted.replace_range(range, &replacement);
+        }
+        formatted
+    }
+
+    #[test]
+    fn keyword_cases() {
+        let text = "select name, type from users where id = 1 Limit 1;";
+        let cases = [
+            (
+                KeywordCase::Upper,
+                "SELECT name, type FROM users WHERE id = 1 LIMIT 1;",
+            ),
+            (
+                KeywordCase::Lower,
+                "select name, type from users where id = 1 limit 1;",
+            ),
+            (KeywordCase::Preserve, text),
+        ];
+
+        for (case, expected) in cases {
+            assert_eq!(format(text, case), expected, "{:?}", case);
+        }
+
+        let statements = [
+            (
+                "create view v as select id from t",
+                "CREATE view v AS SELECT id FROM t",
+            ),
+            (
+                "create table c as select * from t",
+                "CREATE TABLE c AS SELECT * FROM t",
+            ),
+            (
+                "create materialized view m as select id as from from t",
+                "CREATE materialized view m AS SELECT id AS from FROM t",
+            ),
+            (
+                "with ids as (select id from t) select * from ids",
+                "WITH ids AS (SELECT id FROM t) SELECT * FROM ids",
+            ),
+            (
+                "select cast(id as text) as select from t",
+                "SELECT CAST(id AS text) AS select FROM t",
+            ),
+            (
+                "insert into t (id) values (1) returning id as from",
+                "insert INTO t (id) values (1) RETURNING id AS from",
+            ),
+        ];
+
+        for (text, expected) in statements {
+            assert_eq!(format(text, KeywordCase::Upper), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn column_labels_are_not_changed() {
+        assert_eq!(
+            format("select t.from, 1 as select from t", KeywordCase::Upper),
+            "SELECT t.from, 1 AS select FROM t"
+        );
+    }
+
+    #[test]
+    fn comments_and_literals_are_preserved() {
+        let text = "-- select from where\nselect 'select from' as \"from\" /* where */ from t;";
+        assert_eq!(
+            format(text, KeywordCase::Upper),
+            "-- select from where\nSELECT 'select from' AS \"from\" /* where */ FROM t;"
+        );
+        assert_eq!(
+            format(&text.to_uppercase(), KeywordCase::Lower),
+            "-- SELECT FROM WHERE\nselect 'SELECT FROM' as \"FROM\" /* WHERE */ from T;"
+        );
+    }
+
+    #[test]
+    fn invalid_source_is_left_alone() {
+        assert!(keyword_case_edits("select 'unterminated", KeywordCase::Upper).is_empty());
+    }
+}
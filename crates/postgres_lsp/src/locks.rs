@@ -0,0 +1,724 @@
+use std::fmt;
+
+use pg_query::{
+    protobuf::{AlterTableType, ConstrType, Constraint, ObjectType, RangeVar, ReindexObjectType},
+    Node, NodeEnum, NodeRef,
+};
+
+use crate::statement_info::{qualified_name, range_var_name};
+
+/// Table-level lock modes, ordered from weakest to strongest
+///
+/// See <https://www.postgresql.org/docs/current/explicit-locking.html#LOCKING-TABLES>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LockMode {
+    AccessShare,
+    RowShare,
+    RowExclusive,
+    ShareUpdateExclusive,
+    Share,
+    ShareRowExclusive,
+    Exclusive,
+    AccessExclusive,
+}
+
+impl LockMode {
+    /// `LockStmt.mode` uses the lock mode numbers of `lockdefs.h`
+    fn from_lock_stmt_mode(mode: i32) -> Option<LockMode> {
+        match mode {
+            1 => Some(LockMode::AccessShare),
+            2 => Some(LockMode::RowShare),
+            3 => Some(LockMode::RowExclusive),
+            4 => Some(LockMode::ShareUpdateExclusive),
+            5 => Some(LockMode::Share),
+            6 => Some(LockMode::ShareRowExclusive),
+            7 => Some(LockMode::Exclusive),
+            8 => Some(LockMode::AccessExclusive),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LockMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LockMode::AccessShare => "ACCESS SHARE",
+            LockMode::RowShare => "ROW SHARE",
+            LockMode::RowExclusive => "ROW EXCLUSIVE",
+            LockMode::ShareUpdateExclusive => "SHARE UPDATE EXCLUSIVE",
+            LockMode::Share => "SHARE",
+            LockMode::ShareRowExclusive => "SHARE ROW EXCLUSIVE",
+            LockMode::Exclusive => "EXCLUSIVE",
+            LockMode::AccessExclusive => "ACCESS EXCLUSIVE",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A lock a statement takes on a relation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelationLock {
+    pub relation: String,
+    pub mode: LockMode,
+}
+
+impl RelationLock {
+    fn new(relation: &RangeVar, mode: LockMode) -> RelationLock {
+        RelationLock {
+            relation: range_var_name(relation),
+            mode,
+        }
+    }
+}
+
+/// Returns the table-level locks a DDL or maintenance statement acquires
+///
+/// Plain DML is ignored since its locks do not conflict with other DML.
+pub fn statement_locks(stmt: &NodeEnum) -> Vec<RelationLock> {
+    let mut locks = Vec::new();
+
+    match stmt {
+        NodeEnum::AlterTableStmt(s) => {
+            let mut mode = LockMode::ShareUpdateExclusive;
+            for cmd in s.cmds.iter() {
+                let Some(NodeEnum::AlterTableCmd(cmd)) = &cmd.node else {
+                    continue;
+                };
+                let subtype = AlterTableType::from_i32(cmd.subtype).unwrap_or_default();
+                mode = mode.max(alter_table_cmd_lock(subtype, cmd.def.as_deref()));
+            }
+            if let Some(relation) = &s.relation {
+                locks.push(RelationLock::new(relation, mode));
+            }
+            locks.extend(foreign_key_locks(stmt));
+        }
+        NodeEnum::CreateStmt(_) => locks.extend(foreign_key_locks(stmt)),
+        NodeEnum::IndexStmt(s) => {
+            if let Some(relation) = &s.relation {
+                let mode = if s.concurrent {
+                    LockMode::ShareUpdateExclusive
+                } else {
+                    LockMode::Share
+                };
+                locks.push(RelationLock::new(relation, mode));
+            }
+        }
+        NodeEnum::DropStmt(s) => {
+            let remove_type = ObjectType::from_i32(s.remove_type).unwrap_or_default();
+            let mode = if s.concurrent {
+                LockMode::ShareUpdateExclusive
+            } else {
+                LockMode::AccessExclusive
+            };
+            for object in s.objects.iter() {
+                let Some(NodeEnum::List(list)) = &object.node else {
+                    continue;
+                };
+                let relation = match remove_type {
+                    ObjectType::ObjectTable
+                    | ObjectType::ObjectView
+                    | ObjectType::ObjectMatview
+                    | ObjectType::ObjectIndex
+                    | ObjectType::ObjectSequence
+                    | ObjectType::ObjectForeignTable => qualified_name(&list.items),
+                    // the last item is the name of the trigger, rule or policy
+                    ObjectType::ObjectTrigger
+                    | ObjectType::ObjectRule
+                    | ObjectType::ObjectPolicy => {
+                        qualified_name(&list.items[..list.items.len().saturating_sub(1)])
+                    }
+                    _ => continue,
+                };
+                locks.push(RelationLock { relation, mode });
+            }
+        }
+        NodeEnum::TruncateStmt(s) => {
+            for relation in s.relations.iter() {
+                if let Some(NodeEnum::RangeVar(r)) = &relation.node {
+                    locks.push(RelationLock::new(r, LockMode::AccessExclusive));
+                }
+            }
+        }
+        NodeEnum::VacuumStmt(s) => {
+            let mode = if has_option(&s.options, "full") {
+                LockMode::AccessExclusive
+            } else {
+                LockMode::ShareUpdateExclusive
+            };
+            for rel in s.rels.iter() {
+                if let Some(NodeEnum::VacuumRelation(v)) = &rel.node {
+                    if let Some(relation) = &v.relation {
+                        locks.push(RelationLock::new(relation, mode));
+                    }
+                }
+            }
+        }
+        NodeEnum::ClusterStmt(s) => {
+            if let Some(relation) = &s.relation {
+                locks.push(RelationLock::new(relation, LockMode::AccessExclusive));
+            }
+        }
+        NodeEnum::ReindexStmt(s) => {
+            if let Some(relation) = &s.relation {
+                let mode = if has_option(&s.params, "concurrently") {
+                    LockMode::ShareUpdateExclusive
+                } else if s.kind == ReindexObjectType::ReindexObjectIndex as i32 {
+                    LockMode::AccessExclusive
+                } else {
+                    LockMode::Share
+                };
+                locks.push(RelationLock::new(relation, mode));
+            }
+        }
+        NodeEnum::RefreshMatViewStmt(s) => {
+            if let Some(relation) = &s.relation {
+                let mode = if s.concurrent {
+                    LockMode::Exclusive
+                } else {
+                    LockMode::AccessExclusive
+                };
+                locks.push(RelationLock::new(relation, mode));
+            }
+        }
+        NodeEnum::CreateTrigStmt(s) => {
+            if let Some(relation) = &s.relation {
+                locks.push(RelationLock::new(relation, LockMode::ShareRowExclusive));
+            }
+        }
+        NodeEnum::RuleStmt(s) => {
+            if let Some(relation) = &s.relation {
+                locks.push(RelationLock::new(relation, LockMode::AccessExclusive));
+            }
+        }
+        NodeEnum::CreatePolicyStmt(s) => {
+            if let Some(relation) = &s.table {
+                locks.push(RelationLock::new(relation, LockMode::AccessExclusive));
+            }
+        }
+        NodeEnum::AlterPolicyStmt(s) => {
+            if let Some(relation) = &s.table {
+                locks.push(RelationLock::new(relation, LockMode::AccessExclusive));
+            }
+        }
+        NodeEnum::RenameStmt(s) => {
+            if let Some(relation) = &s.relation {
+                locks.push(RelationLock::new(relation, LockMode::AccessExclusive));
+            }
+        }
+        NodeEnum::LockStmt(s) => {
+            if let Some(mode) = LockMode::from_lock_stmt_mode(s.mode) {
+                for relation in s.relations.iter() {
+                    if let Some(NodeEnum::RangeVar(r)) = &relation.node {
+                        locks.push(RelationLock::new(r, mode));
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+
+    locks
+}
+
+/// A relation that a statement rewrites entirely, together with the reason
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableRewrite {
+    pub relation: String,
+    pub reason: &'static str,
+}
+
+/// Volatile functions that are evaluated for every row when used as the default of a new column,
+/// which forces a rewrite
+///
+/// Other functions are assumed not to be volatile since most functions used as defaults, such as
+/// `now()`, are not.
+const VOLATILE_FUNCTIONS: &[&str] = &[
+    "random",
+    "random_normal",
+    "gen_random_uuid",
+    "uuid_generate_v1",
+    "uuid_generate_v1mc",
+    "uuid_generate_v4",
+    "clock_timestamp",
+    "timeofday",
+    "nextval",
+];
+
+/// Serial types are backed by a sequence, i.e. their default is `nextval(...)`
+const SERIAL_TYPES: &[&str] = &[
+    "smallserial",
+    "serial2",
+    "serial",
+    "serial4",
+    "bigserial",
+    "serial8",
+];
+
+/// Returns the relations a statement rewrites, e.g. by changing the type of a column
+///
+/// Adding a column with a non-volatile default does not rewrite the table since Postgres 11, which
+/// is assumed if `target_version` is not set.
+pub fn statement_rewrites(stmt: &NodeEnum, target_version: Option<u32>) -> Vec<TableRewrite> {
+    let (relation, reasons) = match stmt {
+        NodeEnum::AlterTableStmt(s) => {
+            let reasons = s
+                .cmds
+                .iter()
+                .filter_map(|cmd| match &cmd.node {
+                    Some(NodeEnum::AlterTableCmd(cmd)) => alter_table_cmd_rewrite(
+                        AlterTableType::from_i32(cmd.subtype).unwrap_or_default(),
+                        cmd.def.as_deref(),
+                        target_version,
+                    ),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            (s.relation.as_ref(), reasons)
+        }
+        NodeEnum::VacuumStmt(s) if has_option(&s.options, "full") => {
+            let rewrites = s
+                .rels
+                .iter()
+                .filter_map(|rel| match &rel.node {
+                    Some(NodeEnum::VacuumRelation(v)) => v.relation.as_ref(),
+                    _ => None,
+                })
+                .map(|relation| TableRewrite {
+                    relation: range_var_name(relation),
+                    reason: "VACUUM FULL",
+                })
+                .collect();
+            return rewrites;
+        }
+        NodeEnum::ClusterStmt(s) => (s.relation.as_ref(), vec!["CLUSTER"]),
+        _ => return Vec::new(),
+    };
+
+    let Some(relation) = relation else {
+        return Vec::new();
+    };
+    reasons
+        .into_iter()
+        .map(|reason| TableRewrite {
+            relation: range_var_name(relation),
+            reason,
+        })
+        .collect()
+}
+
+fn alter_table_cmd_rewrite(
+    subtype: AlterTableType,
+    def: Option<&Node>,
+    target_version: Option<u32>,
+) -> Option<&'static str> {
+    match subtype {
+        // binary coercible changes, e.g. from `varchar(10)` to `text`, do not need a rewrite, but
+        // that cannot be told from the statement alone
+        AlterTableType::AtAlterColumnType => Some("column type change"),
+        AlterTableType::AtSetTableSpace => Some("SET TABLESPACE"),
+        AlterTableType::AtSetLogged | AlterTableType::AtSetUnLogged => Some("SET LOGGED/UNLOGGED"),
+        AlterTableType::AtSetAccessMethod => Some("SET ACCESS METHOD"),
+        AlterTableType::AtAddColumn => {
+            let Some(NodeEnum::ColumnDef(column)) = def.and_then(|d| d.node.as_ref()) else {
+                return None;
+            };
+            let type_name = column
+                .type_name
+                .as_ref()
+                .map(|t| qualified_name(&t.names))
+                .unwrap_or_default();
+            if SERIAL_TYPES.contains(&type_name.as_str()) {
+                return Some("new serial column");
+            }
+            column.constraints.iter().find_map(|c| match &c.node {
+                Some(NodeEnum::Constraint(c)) => new_column_rewrite(c, target_version),
+                _ => None,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn new_column_rewrite(
+    constraint: &Constraint,
+    target_version: Option<u32>,
+) -> Option<&'static str> {
+    match ConstrType::from_i32(constraint.contype)? {
+        ConstrType::ConstrIdentity => Some("new identity column"),
+        ConstrType::ConstrGenerated => Some("new stored generated column"),
+        ConstrType::ConstrDefault => {
+            let expr = constraint.raw_expr.as_ref()?.node.as_ref()?;
+            if target_version.is_some_and(|v| v < 11) {
+                let is_null = matches!(expr, NodeEnum::AConst(c) if c.isnull);
+                return (!is_null).then_some("new column with a default before Postgres 11");
+            }
+            let volatile = expr.nodes().iter().any(|(node, _, _)| match node {
+                NodeRef::FuncCall(f) => {
+                    let name = qualified_name(&f.funcname);
+                    let name = name.rsplit('.').next().unwrap_or_default();
+                    VOLATILE_FUNCTIONS.contains(&name)
+                }
+                _ => false,
+            });
+            volatile.then_some("new column with a volatile default")
+        }
+        _ => None,
+    }
+}
+
+fn alter_table_cmd_lock(subtype: AlterTableType, def: Option<&Node>) -> LockMode {
+    match subtype {
+        AlterTableType::AtSetStatistics
+        | AlterTableType::AtSetOptions
+        | AlterTableType::AtResetOptions
+        | AlterTableType::AtClusterOn
+        | AlterTableType::AtDropCluster
+        | AlterTableType::AtSetRelOptions
+        | AlterTableType::AtResetRelOptions
+        | AlterTableType::AtReplaceRelOptions
+        | AlterTableType::AtValidateConstraint
+        | AlterTableType::AtValidateConstraintRecurse
+        | AlterTableType::AtAttachPartition
+        | AlterTableType::AtDetachPartitionFinalize => LockMode::ShareUpdateExclusive,
+        AlterTableType::AtEnableTrig
+        | AlterTableType::AtEnableAlwaysTrig
+        | AlterTableType::AtEnableReplicaTrig
+        | AlterTableType::AtDisableTrig
+        | AlterTableType::AtEnableTrigAll
+        | AlterTableType::AtDisableTrigAll
+        | AlterTableType::AtEnableTrigUser
+        | AlterTableType::AtDisableTrigUser => LockMode::ShareRowExclusive,
+        AlterTableType::AtAddConstraint | AlterTableType::AtAddConstraintRecurse => {
+            match def.and_then(|d| d.node.as_ref()) {
+                Some(NodeEnum::Constraint(c)) if c.contype == ConstrType::ConstrForeign as i32 => {
+                    LockMode::ShareRowExclusive
+                }
+                _ => LockMode::AccessExclusive,
+            }
+        }
+        _ => LockMode::AccessExclusive,
+    }
+}
+
+/// Foreign keys lock the tables they reference
+fn foreign_key_locks(stmt: &NodeEnum) -> impl Iterator<Item = RelationLock> + '_ {
+    foreign_key_targets(stmt)
+        .into_iter()
+        .map(|pktable| RelationLock::new(pktable, LockMode::ShareRowExclusive))
+}
+
+/// Returns the tables referenced by the foreign keys a `CREATE TABLE` or `ALTER TABLE` adds
+pub fn foreign_key_targets(stmt: &NodeEnum) -> Vec<&RangeVar> {
+    foreign_keys(stmt)
+        .into_iter()
+        .filter_map(|c| c.pktable.as_ref())
+        .collect()
+}
+
+/// Returns the foreign keys a `CREATE TABLE` or `ALTER TABLE` adds, as column or table
+/// constraints
+pub fn foreign_keys(stmt: &NodeEnum) -> Vec<&Constraint> {
+    let mut elements = Vec::new();
+    match stmt {
+        NodeEnum::CreateStmt(s) => elements.extend(s.table_elts.iter()),
+        NodeEnum::AlterTableStmt(s) => {
+            elements.extend(s.cmds.iter().filter_map(|cmd| match &cmd.node {
+                Some(NodeEnum::AlterTableCmd(cmd)) => cmd.def.as_deref(),
+                _ => None,
+            }))
+        }
+        _ => {}
+    }
+
+    let mut constraints = Vec::new();
+    for element in elements {
+        match &element.node {
+            Some(NodeEnum::ColumnDef(c)) => {
+                constraints.extend(c.constraints.iter().filter_map(|c| match &c.node {
+                    Some(NodeEnum::Constraint(c)) => Some(c),
+                    _ => None,
+                }))
+            }
+            Some(NodeEnum::Constraint(c)) => constraints.push(c),
+            _ => {}
+        }
+    }
+
+    constraints
+        .into_iter()
+        .filter(|c| c.contype == ConstrType::ConstrForeign as i32)
+        .map(|c| c.as_ref())
+        .collect()
+}
+
+/// Checks whether a list of `DefElem` options, e.g. of `VACUUM (FULL)`, contains `name`
+pub fn has_option(options: &[Node], name: &str) -> bool {
+    options.iter().any(|o| match &o.node {
+        Some(NodeEnum::DefElem(d)) => d.defname == name,
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{statement_locks, statement_rewrites, LockMode, RelationLock, TableRewrite};
+    use crate::test_utils::parse;
+
+    fn lock(relation: &str, mode: LockMode) -> RelationLock {
+        RelationLock {
+            relation: relation.to_string(),
+            mode,
+        }
+    }
+
+    #[test]
+    fn locks_per_statement() {
+        use LockMode::*;
+
+        let cases = [
+            ("select * from t", vec![]),
+            ("insert into t values (1)", vec![]),
+            (
+                "alter table t set (fillfactor = 70)",
+                vec![lock("t", ShareUpdateExclusive)],
+            ),
+            (
+                "alter table t alter column c set statistics 100",
+                vec![lock("t", ShareUpdateExclusive)],
+            ),
+            (
+                "alter table t disable trigger all",
+                vec![lock("t", ShareRowExclusive)],
+            ),
+            (
+                "alter table t add column c int",
+                vec![lock("t", AccessExclusive)],
+            ),
+            (
+                "alter table t set (fillfactor = 70), add column c int",
+                vec![lock("t", AccessExclusive)],
+            ),
+            (
+                "alter table t add constraint fk foreign key (c) references s (id)",
+                vec![lock("t", ShareRowExclusive), lock("s", ShareRowExclusive)],
+            ),
+            (
+                "alter table t add constraint c_check check (c > 0)",
+                vec![lock("t", AccessExclusive)],
+            ),
+            (
+                "create table t (id int references s (id))",
+                vec![lock("s", ShareRowExclusive)],
+            ),
+            ("create index i on t (c)", vec![lock("t", Share)]),
+            (
+                "create index concurrently i on t (c)",
+                vec![lock("t", ShareUpdateExclusive)],
+            ),
+            (
+                "drop table t, app.s",
+                vec![lock("t", AccessExclusive), lock("app.s", AccessExclusive)],
+            ),
+            (
+                "drop index concurrently i",
+                vec![lock("i", ShareUpdateExclusive)],
+            ),
+            (
+                "drop trigger tr on app.t",
+                vec![lock("app.t", AccessExclusive)],
+            ),
+            ("drop policy p on t", vec![lock("t", AccessExclusive)]),
+            ("drop function f()", vec![]),
+            (
+                "truncate t, s",
+                vec![lock("t", AccessExclusive), lock("s", AccessExclusive)],
+            ),
+            ("vacuum t", vec![lock("t", ShareUpdateExclusive)]),
+            ("vacuum full t", vec![lock("t", AccessExclusive)]),
+            ("cluster t using i", vec![lock("t", AccessExclusive)]),
+            ("reindex table t", vec![lock("t", Share)]),
+            ("reindex index i", vec![lock("i", AccessExclusive)]),
+            (
+                "reindex table concurrently t",
+                vec![lock("t", ShareUpdateExclusive)],
+            ),
+            (
+                "refresh materialized view m",
+                vec![lock("m", AccessExclusive)],
+            ),
+            (
+                "refresh materialized view concurrently m",
+                vec![lock("m", Exclusive)],
+            ),
+            (
+                "create trigger tr after insert on t for each row execute function f()",
+                vec![lock("t", ShareRowExclusive)],
+            ),
+            (
+                "create rule r as on insert to t do nothing",
+                vec![lock("t", AccessExclusive)],
+            ),
+            (
+                "create policy p on t using (true)",
+                vec![lock("t", AccessExclusive)],
+            ),
+            (
+                "alter policy p on t using (false)",
+                vec![lock("t", AccessExclusive)],
+            ),
+            (
+                "alter table t rename to s",
+                vec![lock("t", AccessExclusive)],
+            ),
+        ];
+
+        for (sql, expected) in cases {
+            assert_eq!(statement_locks(&parse(sql)), expected, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn lock_statement_modes() {
+        use LockMode::*;
+
+        let cases = [
+            ("access share", AccessShare),
+            ("row share", RowShare),
+            ("row exclusive", RowExclusive),
+            ("share update exclusive", ShareUpdateExclusive),
+            ("share", Share),
+            ("share row exclusive", ShareRowExclusive),
+            ("exclusive", Exclusive),
+            ("access exclusive", AccessExclusive),
+        ];
+
+        for (mode, expected) in cases {
+            let sql = format!("lock table t, s in {} mode", mode);
+            assert_eq!(
+                statement_locks(&parse(&sql)),
+                vec![lock("t", expected), lock("s", expected)],
+                "{}",
+                sql
+            );
+        }
+        assert_eq!(
+            statement_locks(&parse("lock table t")),
+            vec![lock("t", AccessExclusive)]
+        );
+    }
+
+    #[test]
+    fn rewrites_per_statement() {
+        let cases: [(&str, Option<u32>, &[&str]); 22] = [
+            ("alter table t add column c int", None, &[]),
+            ("alter table t add column c int default 0", None, &[]),
+            (
+                "alter table t add column c timestamptz default now()",
+                None,
+                &[],
+            ),
+            (
+                "alter table t add column c text default current_setting('app.tenant')",
+                None,
+                &[],
+            ),
+            (
+                "alter table t add column c text default lower('A')",
+                None,
+                &[],
+            ),
+            (
+                "alter table t add column c float default random()",
+                None,
+                &["new column with a volatile default"],
+            ),
+            (
+                "alter table t add column c uuid default gen_random_uuid()",
+                None,
+                &["new column with a volatile default"],
+            ),
+            (
+                "alter table t add column c uuid default public.uuid_generate_v4()",
+                None,
+                &["new column with a volatile default"],
+            ),
+            (
+                "alter table t add column c timestamptz default clock_timestamp()",
+                None,
+                &["new column with a volatile default"],
+            ),
+            (
+                "alter table t add column c bigint default nextval('s')",
+                None,
+                &["new column with a volatile default"],
+            ),
+            (
+                "alter table t add column c serial",
+                None,
+                &["new serial column"],
+            ),
+            (
+                "alter table t add column c bigserial",
+                None,
+                &["new serial column"],
+            ),
+            (
+                "alter table t add column c int default 0",
+                Some(10),
+                &["new column with a default before Postgres 11"],
+            ),
+            ("alter table t add column c int default null", Some(10), &[]),
+            (
+                "alter table t add column c int generated always as identity",
+                None,
+                &["new identity column"],
+            ),
+            (
+                "alter table t add column c int generated always as (id * 2) stored",
+                None,
+                &["new stored generated column"],
+            ),
+            (
+                "alter table t alter column c type bigint",
+                None,
+                &["column type change"],
+            ),
+            (
+                "alter table t set tablespace fast, set unlogged",
+                None,
+                &["SET TABLESPACE", "SET LOGGED/UNLOGGED"],
+            ),
+            ("vacuum t", None, &[]),
+            ("vacuum full t", None, &["VACUUM FULL"]),
+            ("cluster t using i", None, &["CLUSTER"]),
+            ("create index i on t (c)", None, &[]),
+        ];
+
+        for (sql, target_version, reasons) in cases {
+            let expected = reasons
+                .iter()
+                .map(|&reason| TableRewrite {
+                    relation: "t".to_string(),
+                    reason,
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                statement_rewrites(&parse(sql), target_version),
+                expected,
+                "{}",
+                sql
+            );
+        }
+    }
+
+    #[test]
+    fn lock_modes_are_ordered_by_strength() {
+        assert!(LockMode::AccessShare < LockMode::RowExclusive);
+        assert!(LockMode::ShareUpdateExclusive < LockMode::Share);
+        assert!(LockMode::Exclusive < LockMode::AccessExclusive);
+        assert_eq!(
+            LockMode::ShareRowExclusive.to_string(),
+            "SHARE ROW EXCLUSIVE"
+        );
+    }
+}
@@ -1,17 +1,72 @@
+mod definitions;
+mod foreign_keys;
+mod formatting;
+mod locks;
+mod performance;
+mod policy_template;
+mod safety;
 mod semantic_token;
+mod signature_help;
+mod statement_info;
+mod storage_parameters;
+#[cfg(test)]
+mod test_utils;
+mod transactions;
+mod typecheck;
 mod utils;
 
 use dashmap::DashMap;
-use parser::{parse_source, Parse};
+use parser::{parse_source, Parse, SyntaxKind};
+use pg_query::NodeEnum;
 use ropey::Rope;
 use semantic_token::{ImCompleteSemanticToken, LEGEND_TYPE};
+use serde::Deserialize;
 use serde_json::Value;
-use tower_lsp::jsonrpc::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tower_lsp::jsonrpc::{Error, Result};
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService, Server};
+use tracing_subscriber::fmt::{self, format::DefaultFields, writer::BoxMakeWriter};
+use tracing_subscriber::{layer::Layered, prelude::*, reload, EnvFilter, Registry};
 
+use crate::definitions::{
+    column_at_offset, column_names_referring_to, leading_comment, quote_identifier,
+    reference_at_offset, references_to, relation_at_offset, relation_names_referring_to,
+    statement_symbol, DefinedObject,
+};
+use crate::foreign_keys::on_delete_problems;
+use crate::formatting::{keyword_case_edits, KeywordCase};
+use crate::locks::{statement_locks, statement_rewrites};
+use crate::performance::view_selects_star;
+use crate::policy_template::{policy_template, table_columns};
+use crate::safety::{
+    policy_uses_spoofable_claims, protected_objects, replicated_schema_references,
+};
 use crate::semantic_token::semantic_token_from_syntax_kind;
-use crate::utils::offset_to_position;
+use crate::signature_help::{call_at_end, function_signature};
+use crate::statement_info::{StatementInfo, StatementInfoParams};
+use crate::storage_parameters::unknown_storage_parameters;
+use crate::transactions::statements_outside_transaction;
+use crate::typecheck::insert_arity_errors;
+use crate::utils::{offset_to_position, position_to_offset, statement_byte_range, statement_range};
+
+/// Returns the statement at a position with its constants replaced by parameters, e.g. to look
+/// it up in `pg_stat_statements`
+const NORMALIZE_STATEMENT_COMMAND: &str = "postgres_lsp.normalizeStatement";
+/// Returns the pg_query fingerprint of the statement at a position
+const FINGERPRINT_STATEMENT_COMMAND: &str = "postgres_lsp.fingerprintStatement";
+
+/// An object created by a statement of an open document
+#[derive(Debug)]
+struct Definition {
+    object: DefinedObject,
+    /// The range of the statement that creates the object
+    range: Range,
+    /// The index of the statement in the parse of the document
+    stmt_idx: usize,
+}
 
 #[derive(Debug)]
 struct Backend {
@@ -19,19 +74,165 @@ struct Backend {
     parse_map: DashMap<String, Parse>,
     document_map: DashMap<String, Rope>,
     semantic_token_map: DashMap<String, Vec<ImCompleteSemanticToken>>,
+    /// The objects each open document defines
+    definition_map: DashMap<String, Vec<Definition>>,
+    options: RwLock<InitializationOptions>,
+    log_filter: reload::Handle<EnvFilter, Registry>,
+    log_writer: reload::Handle<LogLayer, LogSubscriber>,
+}
+
+type LogSubscriber = Layered<reload::Layer<EnvFilter, Registry>, Registry>;
+type LogLayer = fmt::Layer<LogSubscriber, DefaultFields, fmt::format::Format, BoxMakeWriter>;
+
+/// Returns the layer that writes logs to `writer`
+fn log_layer(writer: BoxMakeWriter) -> LogLayer {
+    fmt::layer().with_ansi(false).with_writer(writer)
+}
+
+/// Opens the file logs are appended to
+fn open_log_file(path: &Path) -> std::io::Result<std::fs::File> {
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+}
+
+/// Options passed by the client in the `initialize` request
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct InitializationOptions {
+    /// Overrides the `POSTGRES_LSP_LOG` filter, e.g. `debug` or `postgres_lsp=trace`
+    log_level: Option<String>,
+    /// Overrides `POSTGRES_LSP_LOG_FILE`, the file logs are appended to instead of stderr
+    log_file: Option<PathBuf>,
+    /// Whether to report the locks DDL statements take as diagnostics
+    lock_analysis: bool,
+    /// Schemas that are managed by the platform and must not be modified by migrations
+    protected_schemas: Vec<String>,
+    /// Schemas that are managed externally, e.g. by logical replication, and should not be
+    /// depended on by objects in other schemas
+    replicated_schemas: Vec<String>,
+    /// Columns that hold the owner of a row, used to pre-fill policy templates
+    policy_owner_columns: Vec<String>,
+    database: DatabaseOptions,
+    foreign_keys: ForeignKeyOptions,
+    formatting: FormattingOptions,
+}
+
+impl Default for InitializationOptions {
+    fn default() -> Self {
+        InitializationOptions {
+            log_level: None,
+            log_file: None,
+            lock_analysis: false,
+            protected_schemas: vec![
+                "auth".to_string(),
+                "storage".to_string(),
+                "extensions".to_string(),
+            ],
+            replicated_schemas: Vec::new(),
+            policy_owner_columns: vec!["user_id".to_string(), "owner_id".to_string()],
+            database: DatabaseOptions::default(),
+            foreign_keys: ForeignKeyOptions::default(),
+            formatting: FormattingOptions::default(),
+        }
+    }
+}
+
+impl InitializationOptions {
+    /// Parses the options sent by the client
+    ///
+    /// Options with an invalid value are ignored with a warning, so that they do not discard the
+    /// valid ones.
+    fn from_value(value: Value) -> InitializationOptions {
+        let Value::Object(mut options) = value else {
+            tracing::warn!(%value, "initialization options must be an object");
+            return InitializationOptions::default();
+        };
+        options.retain(|key, value| {
+            let option = Value::Object([(key.clone(), value.clone())].into_iter().collect());
+            match serde_json::from_value::<InitializationOptions>(option) {
+                Ok(_) => true,
+                Err(err) => {
+                    tracing::warn!(%err, option = %key, "ignoring invalid initialization option");
+                    false
+                }
+            }
+        });
+        serde_json::from_value(Value::Object(options)).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct DatabaseOptions {
+    /// The major version of the Postgres server the statements will run on, e.g. `15`. The
+    /// latest version is assumed if it is not set.
+    target_version: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ForeignKeyOptions {
+    /// Whether new foreign keys must state what happens when the referenced row is deleted,
+    /// instead of relying on the implicit `NO ACTION`
+    require_on_delete: bool,
+    /// `ON DELETE` actions new foreign keys must not use, e.g. `cascade`
+    disallowed_on_delete_actions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct FormattingOptions {
+    /// The case of reserved keywords, `upper` by default
+    keyword_case: KeywordCase,
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for Backend {
-    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
-        self.client
-            .log_message(MessageType::INFO, "initializing!")
-            .await;
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        tracing::debug!("initializing!");
+        let options = params
+            .initialization_options
+            .map(InitializationOptions::from_value)
+            .unwrap_or_default();
+        if let Some(level) = &options.log_level {
+            match EnvFilter::try_new(level) {
+                Ok(filter) => {
+                    if let Err(err) = self.log_filter.reload(filter) {
+                        tracing::error!(%err, "failed to set log level");
+                    }
+                }
+                Err(err) => tracing::warn!(%err, %level, "invalid log level"),
+            }
+        }
+        if let Some(path) = &options.log_file {
+            match open_log_file(path) {
+                Ok(file) => {
+                    let writer = BoxMakeWriter::new(std::sync::Mutex::new(file));
+                    if let Err(err) = self.log_writer.modify(|layer| *layer.writer_mut() = writer) {
+                        tracing::error!(%err, "failed to set log file");
+                    }
+                }
+                Err(err) => tracing::warn!(%err, path = %path.display(), "failed to open log file"),
+            }
+        }
+        *self.options.write().unwrap() = options;
         Ok(InitializeResult {
             server_info: None,
             offset_encoding: None,
             capabilities: ServerCapabilities {
                 // inlay_hint_provider: Some(OneOf::Left(true)),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                signature_help_provider: Some(SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+                    retrigger_characters: None,
+                    work_done_progress_options: Default::default(),
+                }),
+                document_symbol_provider: Some(OneOf::Left(true)),
+                document_formatting_provider: Some(OneOf::Left(true)),
+                document_range_formatting_provider: Some(OneOf::Left(true)),
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
                     TextDocumentSyncKind::FULL,
                 )),
@@ -42,10 +243,13 @@ impl LanguageServer for Backend {
                 //     all_commit_characters: None,
                 //     completion_item: None,
                 // }),
-                // execute_command_provider: Some(ExecuteCommandOptions {
-                //     commands: vec!["dummy.do_something".to_string()],
-                //     work_done_progress_options: Default::default(),
-                // }),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: vec![
+                        NORMALIZE_STATEMENT_COMMAND.to_string(),
+                        FINGERPRINT_STATEMENT_COMMAND.to_string(),
+                    ],
+                    work_done_progress_options: Default::default(),
+                }),
                 workspace: Some(WorkspaceServerCapabilities {
                     workspace_folders: Some(WorkspaceFoldersServerCapabilities {
                         supported: Some(true),
@@ -79,18 +283,16 @@ impl LanguageServer for Backend {
                     ),
                 ),
                 // definition: Some(GotoCapability::default()),
-                // definition_provider: Some(OneOf::Left(true)),
-                // references_provider: Some(OneOf::Left(true)),
-                // rename_provider: Some(OneOf::Left(true)),
+                definition_provider: Some(OneOf::Left(true)),
+                references_provider: Some(OneOf::Left(true)),
+                rename_provider: Some(OneOf::Left(true)),
                 ..ServerCapabilities::default()
             },
         })
     }
 
     async fn initialized(&self, _: InitializedParams) {
-        self.client
-            .log_message(MessageType::INFO, "initialized!")
-            .await;
+        tracing::debug!("initialized!");
     }
 
     async fn shutdown(&self) -> Result<()> {
@@ -98,9 +300,7 @@ impl LanguageServer for Backend {
     }
 
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
-        self.client
-            .log_message(MessageType::INFO, "file opened!")
-            .await;
+        tracing::debug!("file opened!");
         self.on_change(TextDocumentItem {
             uri: params.text_document.uri,
             text: params.text_document.text,
@@ -110,9 +310,7 @@ impl LanguageServer for Backend {
     }
 
     async fn did_change(&self, mut params: DidChangeTextDocumentParams) {
-        self.client
-            .log_message(MessageType::INFO, "file changed!")
-            .await;
+        tracing::debug!("file changed!");
         self.on_change(TextDocumentItem {
             uri: params.text_document.uri,
             text: std::mem::take(&mut params.content_changes[0].text),
@@ -122,14 +320,267 @@ impl LanguageServer for Backend {
     }
 
     async fn did_save(&self, _: DidSaveTextDocumentParams) {
-        self.client
-            .log_message(MessageType::INFO, "file saved!")
-            .await;
+        tracing::debug!("file saved!");
     }
     async fn did_close(&self, _: DidCloseTextDocumentParams) {
-        self.client
-            .log_message(MessageType::INFO, "file closed!")
-            .await;
+        tracing::debug!("file closed!");
+    }
+
+    #[tracing::instrument(skip_all, fields(uri = %params.text_document_position_params.text_document.uri))]
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let position = params.text_document_position_params;
+        let Some(reference) =
+            self.reference_at_position(position.text_document.uri.as_str(), position.position)
+        else {
+            return Ok(None);
+        };
+
+        let uri = position.text_document.uri.to_string();
+        let local_only = self.references_temporary_object(&uri, &reference);
+
+        // objects defined in open documents are documented by the comment above their definition
+        for entry in self.definition_map.iter() {
+            let is_local = entry.key() == &uri;
+            if local_only && !is_local {
+                continue;
+            }
+            let (Some(parse), Some(rope)) = (
+                self.parse_map.get(entry.key()),
+                self.document_map.get(entry.key()),
+            ) else {
+                continue;
+            };
+            for definition in entry.value().iter() {
+                let object = &definition.object;
+                if !object.matches(&reference) || (object.temporary && !is_local) {
+                    continue;
+                }
+
+                let mut value = format!("```sql\n{}\n```", object.label());
+                if let Some(comment) = parse
+                    .stmts
+                    .get(definition.stmt_idx)
+                    .and_then(|stmt| leading_comment(stmt, &rope))
+                {
+                    value.push_str("\n\n");
+                    value.push_str(&comment);
+                }
+                return Ok(Some(Hover {
+                    contents: HoverContents::Markup(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value,
+                    }),
+                    range: None,
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let Some((table, insert_at)) =
+            self.policy_insert_position(params.text_document.uri.as_str(), params.range.start)
+        else {
+            return Ok(None);
+        };
+
+        // the columns are known if the table is created in one of the open documents
+        let columns = self
+            .definition_map
+            .iter()
+            .flat_map(|entry| {
+                let Some(parse) = self.parse_map.get(entry.key()) else {
+                    return Vec::new();
+                };
+                entry
+                    .value()
+                    .iter()
+                    .filter(|d| d.object.kind == "TABLE" && d.object.matches(&table))
+                    .filter_map(|d| parse.stmts.get(d.stmt_idx))
+                    .flat_map(|s| table_columns(&s.stmt))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let owner_columns = self.options.read().unwrap().policy_owner_columns.clone();
+
+        let edit = TextEdit {
+            range: Range::new(insert_at, insert_at),
+            new_text: format!("\n\n{}", policy_template(&table, &columns, &owner_columns)),
+        };
+        Ok(Some(vec![CodeActionOrCommand::CodeAction(CodeAction {
+            title: format!("Add a policy for {}", table),
+            kind: Some(CodeActionKind::REFACTOR),
+            edit: Some(WorkspaceEdit {
+                changes: Some([(params.text_document.uri, vec![edit])].into()),
+                ..WorkspaceEdit::default()
+            }),
+            ..CodeAction::default()
+        })]))
+    }
+
+    async fn goto_definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> Result<Option<GotoDefinitionResponse>> {
+        let position = params.text_document_position_params;
+        let Some(reference) =
+            self.reference_at_position(position.text_document.uri.as_str(), position.position)
+        else {
+            return Ok(None);
+        };
+
+        let uri = position.text_document.uri.to_string();
+        let local_only = self.references_temporary_object(&uri, &reference);
+
+        let locations = self
+            .definition_map
+            .iter()
+            .filter(|entry| !local_only || entry.key() == &uri)
+            .flat_map(|entry| {
+                let is_local = entry.key() == &uri;
+                let entry_uri = Url::parse(entry.key()).ok();
+                entry
+                    .value()
+                    .iter()
+                    .filter(|d| d.object.matches(&reference) && (is_local || !d.object.temporary))
+                    .filter_map(|d| Some(Location::new(entry_uri.clone()?, d.range)))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        Ok(match locations.len() {
+            0 => None,
+            1 => locations
+                .into_iter()
+                .next()
+                .map(GotoDefinitionResponse::Scalar),
+            _ => Some(GotoDefinitionResponse::Array(locations)),
+        })
+    }
+
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let position = params.text_document_position;
+        let uri = position.text_document.uri.to_string();
+        let Some(reference) = self.reference_at_position(&uri, position.position) else {
+            return Ok(None);
+        };
+
+        let locations = self
+            .reference_locations(
+                &uri,
+                &reference,
+                &[SyntaxKind::RangeVar, SyntaxKind::FuncCall],
+            )
+            .into_iter()
+            .map(|(location, _)| location)
+            // the name in a `CREATE` statement is a reference as well
+            .filter(|location| {
+                params.context.include_declaration || !self.is_declaration(location, &reference)
+            })
+            .collect();
+
+        Ok(Some(locations))
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let position = params.text_document_position;
+        let uri = position.text_document.uri.to_string();
+        let (locations, is_relation) = if let Some(reference) =
+            self.relation_at_position(&uri, position.position)
+        {
+            (self.relation_name_locations(&uri, &reference), true)
+        } else if let Some((relation, column)) = self.column_at_position(&uri, position.position) {
+            (self.column_name_locations(&uri, &relation, &column), false)
+        } else {
+            return Ok(None);
+        };
+        let new_name = match quote_identifier(&params.new_name) {
+            Some(name) if params.new_name.starts_with('"') || !params.new_name.contains('.') => {
+                name
+            }
+            _ => {
+                return Err(Error::invalid_params(
+                    "the new name must be a single identifier",
+                ))
+            }
+        };
+
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        for (location, text) in locations {
+            // relations keep the schema they are qualified with, the names of columns are never
+            // qualified
+            let new_text = match text.rsplit_once('.') {
+                Some((schema, _)) if is_relation => format!("{}.{}", schema, new_name),
+                _ => new_name.clone(),
+            };
+            changes.entry(location.uri).or_default().push(TextEdit {
+                range: location.range,
+                new_text,
+            });
+        }
+
+        Ok(Some(WorkspaceEdit {
+            changes: Some(changes),
+            ..WorkspaceEdit::default()
+        }))
+    }
+
+    async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+        let position = params.text_document_position_params;
+        let uri = position.text_document.uri.to_string();
+        let Some((name, argument)) = self.call_at_position(&uri, position.position) else {
+            return Ok(None);
+        };
+
+        // functions are known if they are created in one of the open documents
+        let signatures = self
+            .definition_map
+            .iter()
+            .flat_map(|entry| {
+                let Some(parse) = self.parse_map.get(entry.key()) else {
+                    return Vec::new();
+                };
+                entry
+                    .value()
+                    .iter()
+                    .filter(|d| d.object.matches(&name))
+                    .filter_map(|d| match &parse.stmts.get(d.stmt_idx)?.stmt {
+                        NodeEnum::CreateFunctionStmt(s) => Some(function_signature(s)),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        if signatures.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(SignatureHelp {
+            signatures,
+            active_signature: None,
+            active_parameter: Some(argument),
+        }))
+    }
+
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
+        Ok(self
+            .document_symbols(params.text_document.uri.as_str())
+            .map(DocumentSymbolResponse::Nested))
+    }
+
+    async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
+        Ok(self.formatting_edits(params.text_document.uri.as_str(), None))
+    }
+
+    async fn range_formatting(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        Ok(self.formatting_edits(params.text_document.uri.as_str(), Some(params.range)))
     }
 
     async fn semantic_tokens_full(
@@ -137,21 +588,31 @@ impl LanguageServer for Backend {
         params: SemanticTokensParams,
     ) -> Result<Option<SemanticTokensResult>> {
         let uri = params.text_document.uri.to_string();
-        self.client
-            .log_message(MessageType::LOG, "semantic_token_full")
-            .await;
+        tracing::trace!("semantic_token_full");
         let semantic_tokens = || -> Option<Vec<SemanticToken>> {
             let mut im_complete_tokens = self.semantic_token_map.get_mut(&uri)?;
             let rope = self.document_map.get(&uri)?;
-            im_complete_tokens.sort_by(|a, b| a.start.cmp(&b.start));
+            im_complete_tokens.sort_by_key(|token| token.start);
             let mut pre_line = 0;
             let mut pre_start = 0;
             let semantic_tokens = im_complete_tokens
                 .iter()
                 .filter_map(|token| {
-                    let line = rope.try_byte_to_line(token.start).ok()? as u32;
-                    let first = rope.try_line_to_char(line as usize).ok()? as u32;
-                    let start = rope.try_byte_to_char(token.start).ok()? as u32 - first;
+                    let start_position = offset_to_position(token.start, &rope)?;
+                    let end_position = offset_to_position(token.start + token.length, &rope)?;
+                    let line = start_position.line;
+                    let start = start_position.character;
+                    // tokens spanning several lines, e.g. block comments, end on the first one
+                    let length = if end_position.line == line {
+                        end_position.character - start
+                    } else {
+                        let line_end = rope.try_line_to_byte(line as usize + 1).ok()?;
+                        rope.get_byte_slice(token.start..line_end)?
+                            .to_string()
+                            .trim_end_matches(['\n', '\r'])
+                            .encode_utf16()
+                            .count() as u32
+                    };
                     let delta_line = line - pre_line;
                     let delta_start = if delta_line == 0 {
                         start - pre_start
@@ -161,7 +622,7 @@ impl LanguageServer for Backend {
                     let ret = Some(SemanticToken {
                         delta_line,
                         delta_start,
-                        length: token.length as u32,
+                        length,
                         token_type: token.token_type as u32,
                         token_modifiers_bitset: 0,
                     });
@@ -172,12 +633,7 @@ impl LanguageServer for Backend {
                 .collect::<Vec<_>>();
             Some(semantic_tokens)
         }();
-        self.client
-            .log_message(
-                MessageType::LOG,
-                format!("semantic_tokens: {:?}", semantic_tokens),
-            )
-            .await;
+        tracing::trace!(?semantic_tokens);
         if let Some(semantic_token) = semantic_tokens {
             return Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
                 result_id: None,
@@ -195,35 +651,42 @@ impl LanguageServer for Backend {
     }
 
     async fn did_change_configuration(&self, _: DidChangeConfigurationParams) {
-        self.client
-            .log_message(MessageType::INFO, "configuration changed!")
-            .await;
+        tracing::debug!("configuration changed!");
     }
 
     async fn did_change_workspace_folders(&self, _: DidChangeWorkspaceFoldersParams) {
-        self.client
-            .log_message(MessageType::INFO, "workspace folders changed!")
-            .await;
+        tracing::debug!("workspace folders changed!");
     }
 
     async fn did_change_watched_files(&self, _: DidChangeWatchedFilesParams) {
-        self.client
-            .log_message(MessageType::INFO, "watched files have changed!")
-            .await;
+        tracing::debug!("watched files have changed!");
     }
 
-    async fn execute_command(&self, _: ExecuteCommandParams) -> Result<Option<Value>> {
-        self.client
-            .log_message(MessageType::INFO, "command executed!")
-            .await;
+    async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<Value>> {
+        let position = match params.arguments.into_iter().next() {
+            Some(arg) => serde_json::from_value::<TextDocumentPositionParams>(arg)
+                .map_err(|err| Error::invalid_params(err.to_string()))?,
+            None => return Err(Error::invalid_params("expected a text document position")),
+        };
+        let Some(text) = self.statement_text(&position) else {
+            return Ok(None);
+        };
 
-        match self.client.apply_edit(WorkspaceEdit::default()).await {
-            Ok(res) if res.applied => self.client.log_message(MessageType::INFO, "applied").await,
-            Ok(_) => self.client.log_message(MessageType::INFO, "rejected").await,
-            Err(err) => self.client.log_message(MessageType::ERROR, err).await,
-        }
+        let result = match params.command.as_str() {
+            NORMALIZE_STATEMENT_COMMAND => pg_query::normalize(&text),
+            FINGERPRINT_STATEMENT_COMMAND => pg_query::fingerprint(&text).map(|f| f.hex),
+            command => {
+                return Err(Error::invalid_params(format!(
+                    "unknown command {}",
+                    command
+                )))
+            }
+        };
 
-        Ok(None)
+        match result {
+            Ok(value) => Ok(Some(Value::String(value))),
+            Err(err) => Err(Error::invalid_params(err.to_string())),
+        }
     }
 }
 
@@ -233,10 +696,8 @@ struct TextDocumentItem {
     version: i32,
 }
 impl Backend {
+    #[tracing::instrument(skip_all, fields(uri = %params.uri, version = params.version))]
     async fn on_change(&self, params: TextDocumentItem) {
-        self.client
-            .log_message(MessageType::INFO, format!("on_change {:?}", params.uri))
-            .await;
         let rope = ropey::Rope::from_str(&params.text);
         self.document_map
             .insert(params.uri.to_string(), rope.clone());
@@ -245,25 +706,34 @@ impl Backend {
 
         let result = parse_source(&params.text);
 
-        dbg!(&result.cst);
+        tracing::trace!(cst = ?result.cst);
+
+        debug_assert!(
+            result.stmts.iter().all(|stmt| {
+                params.text.is_char_boundary(stmt.range.start().into())
+                    && params.text.is_char_boundary(stmt.range.end().into())
+            }),
+            "statement ranges must slice the source on char boundaries"
+        );
 
         // update semantic tokens
         let semantic_tokens = result
             .cst
             .descendants_with_tokens()
-            .filter_map(|item| match semantic_token_from_syntax_kind(item.kind()) {
-                Some(token_type) => Some(ImCompleteSemanticToken {
-                    start: item.text_range().start().into(),
-                    token_type,
-                    length: item.text_range().len().into(),
-                }),
-                None => None,
+            .filter_map(|item| {
+                semantic_token_from_syntax_kind(item.kind()).map(|token_type| {
+                    ImCompleteSemanticToken {
+                        start: item.text_range().start().into(),
+                        token_type,
+                        length: item.text_range().len().into(),
+                    }
+                })
             })
             .collect::<Vec<_>>();
 
         // publish diagnostics
         //
-        let diagnostics = result
+        let mut diagnostics = result
             .errors
             .iter()
             .map(|error| {
@@ -277,6 +747,130 @@ impl Backend {
             })
             .collect::<Vec<_>>();
 
+        for (stmt, description) in statements_outside_transaction(&result.stmts) {
+            let Some(range) = statement_range(stmt, &rope) else {
+                continue;
+            };
+            diagnostics.push(diagnostic(
+                range,
+                DiagnosticSeverity::ERROR,
+                "banConcurrentIndexInTransaction",
+                format!("{} cannot run inside a transaction block", description),
+            ));
+        }
+
+        let options = self.options.read().unwrap().clone();
+        for stmt in result.stmts.iter() {
+            let Some(range) = statement_range(stmt, &rope) else {
+                continue;
+            };
+
+            diagnostics.extend(insert_arity_errors(&stmt.stmt).into_iter().map(|message| {
+                diagnostic(range, DiagnosticSeverity::ERROR, "insertArity", message)
+            }));
+
+            diagnostics.extend(unknown_storage_parameters(&stmt.stmt).into_iter().map(
+                |parameter| {
+                    diagnostic(
+                        range,
+                        DiagnosticSeverity::ERROR,
+                        "unknownStorageParameter",
+                        format!("Unrecognized storage parameter \"{}\"", parameter),
+                    )
+                },
+            ));
+
+            diagnostics.extend(
+                protected_objects(&stmt.stmt, &options.protected_schemas)
+                    .into_iter()
+                    .map(|object| {
+                        diagnostic(
+                            range,
+                            DiagnosticSeverity::WARNING,
+                            "protectedSchema",
+                            format!("Modifies {}, which is managed by the platform", object),
+                        )
+                    }),
+            );
+
+            diagnostics.extend(
+                replicated_schema_references(&stmt.stmt, &options.replicated_schemas)
+                    .into_iter()
+                    .map(|relation| {
+                        diagnostic(
+                            range,
+                            DiagnosticSeverity::WARNING,
+                            "replicatedSchemaReference",
+                            format!("Depends on {} in an externally managed schema", relation),
+                        )
+                    }),
+            );
+
+            diagnostics.extend(
+                on_delete_problems(
+                    &stmt.stmt,
+                    &params.text[statement_byte_range(stmt)],
+                    options.foreign_keys.require_on_delete,
+                    &options.foreign_keys.disallowed_on_delete_actions,
+                )
+                .into_iter()
+                .map(|message| {
+                    diagnostic(
+                        range,
+                        DiagnosticSeverity::WARNING,
+                        "onDeleteAction",
+                        message,
+                    )
+                }),
+            );
+
+            if view_selects_star(&stmt.stmt) {
+                diagnostics.push(diagnostic(
+                    range,
+                    DiagnosticSeverity::WARNING,
+                    "viewSelectStar",
+                    "View selects *. Its columns are fixed when it is created and it fetches \
+                     every column, so list the columns explicitly.",
+                ));
+            }
+
+            if policy_uses_spoofable_claims(&stmt.stmt) {
+                diagnostics.push(diagnostic(
+                    range,
+                    DiagnosticSeverity::WARNING,
+                    "spoofableClaims",
+                    "Policy reads user_metadata, which users can change themselves. Use \
+                     app_metadata or a trusted table instead.",
+                ));
+            }
+
+            if options.lock_analysis {
+                diagnostics.extend(statement_locks(&stmt.stmt).into_iter().map(|lock| {
+                    diagnostic(
+                        range,
+                        DiagnosticSeverity::INFORMATION,
+                        "tableLock",
+                        format!("Locks {} in {} mode", lock.relation, lock.mode),
+                    )
+                }));
+                diagnostics.extend(
+                    statement_rewrites(&stmt.stmt, options.database.target_version)
+                        .into_iter()
+                        .map(|rewrite| {
+                            diagnostic(
+                                range,
+                                DiagnosticSeverity::WARNING,
+                                "tableRewrite",
+                                format!(
+                                    "Rewrites {} because of {}",
+                                    rewrite.relation, rewrite.reason
+                                ),
+                            )
+                        }),
+                );
+            }
+        }
+
         self.client
             .publish_diagnostics(params.uri.clone(), diagnostics, Some(params.version))
             .await;
@@ -284,13 +878,305 @@ impl Backend {
         self.semantic_token_map
             .insert(params.uri.to_string(), semantic_tokens);
 
+        self.definition_map
+            .insert(params.uri.to_string(), definitions(&result, &rope));
+
         self.parse_map.insert(params.uri.to_string(), result);
     }
+
+    /// Returns the name of the relation or function referenced at the given position
+    fn reference_at_position(&self, uri: &str, position: Position) -> Option<String> {
+        let parse = self.parse_map.get(uri)?;
+        let rope = self.document_map.get(uri)?;
+        reference_at_offset(&parse.cst, position_to_offset(position, &rope)?)
+    }
+
+    /// Returns the function whose argument list the given position is in, and the index of the
+    /// argument
+    fn call_at_position(&self, uri: &str, position: Position) -> Option<(String, u32)> {
+        let rope = self.document_map.get(uri)?;
+        let offset = position_to_offset(position, &rope)?;
+        call_at_end(&rope.get_byte_slice(..offset)?.to_string())
+    }
+
+    /// Returns the name of the relation referenced at the given position
+    fn relation_at_position(&self, uri: &str, position: Position) -> Option<String> {
+        let parse = self.parse_map.get(uri)?;
+        let rope = self.document_map.get(uri)?;
+        relation_at_offset(&parse.cst, position_to_offset(position, &rope)?)
+    }
+
+    fn column_at_position(&self, uri: &str, position: Position) -> Option<(String, String)> {
+        let rope = self.document_map.get(uri)?;
+        column_at_offset(&rope.to_string(), position_to_offset(position, &rope)?)
+    }
+
+    /// Returns where the open documents refer to `column` of the same relation as `relation` in the
+    /// document `uri`, together with the text of each reference
+    fn column_name_locations(
+        &self,
+        uri: &str,
+        relation: &str,
+        column: &str,
+    ) -> Vec<(Location, String)> {
+        self.locations(uri, relation, |_, rope| {
+            column_names_referring_to(&rope.to_string(), relation, column)
+        })
+    }
+
+    /// Returns where the open documents refer to the same object as `reference` in the document
+    /// `uri` with a node of one of the `kinds`, together with the text of each reference
+    fn reference_locations(
+        &self,
+        uri: &str,
+        reference: &str,
+        kinds: &[SyntaxKind],
+    ) -> Vec<(Location, String)> {
+        self.locations(uri, reference, |parse, _| {
+            references_to(&parse.cst, reference, kinds)
+                .into_iter()
+                .map(|r| r.start().into()..r.end().into())
+                .collect()
+        })
+    }
+
+    /// Returns where the open documents name the relation `reference` in the document `uri`
+    /// refers to, including qualified column references and statements such as `DROP TABLE`,
+    /// together with the text of each name
+    fn relation_name_locations(&self, uri: &str, reference: &str) -> Vec<(Location, String)> {
+        self.locations(uri, reference, |parse, rope| {
+            relation_names_referring_to(&parse.cst, &rope.to_string(), reference)
+        })
+    }
+
+    /// Returns the locations of the byte `ranges` found in each open document that can refer to
+    /// the same object as `reference` in the document `uri`, together with their text
+    fn locations(
+        &self,
+        uri: &str,
+        reference: &str,
+        ranges: impl Fn(&Parse, &Rope) -> Vec<std::ops::Range<usize>>,
+    ) -> Vec<(Location, String)> {
+        // other documents cannot see a temporary object, nor does it refer to their objects
+        let local_only = self.references_temporary_object(uri, reference);
+
+        let mut locations = Vec::new();
+        for entry in self.parse_map.iter() {
+            if entry.key() != uri
+                && (local_only || self.references_temporary_object(entry.key(), reference))
+            {
+                continue;
+            }
+            let (Some(rope), Ok(entry_uri)) =
+                (self.document_map.get(entry.key()), Url::parse(entry.key()))
+            else {
+                continue;
+            };
+            for range in ranges(entry.value(), &rope) {
+                let (Some(start_position), Some(end_position), Some(text)) = (
+                    offset_to_position(range.start, &rope),
+                    offset_to_position(range.end, &rope),
+                    rope.get_byte_slice(range),
+                ) else {
+                    continue;
+                };
+                locations.push((
+                    Location::new(entry_uri.clone(), Range::new(start_position, end_position)),
+                    text.to_string(),
+                ));
+            }
+        }
+        locations
+    }
+
+    /// Checks whether `location` is within a statement that creates the object `reference` refers
+    /// to
+    fn is_declaration(&self, location: &Location, reference: &str) -> bool {
+        self.definition_map
+            .get(location.uri.as_str())
+            .is_some_and(|definitions| {
+                definitions.iter().any(|d| {
+                    d.object.matches(reference)
+                        && d.range.start <= location.range.start
+                        && location.range.end <= d.range.end
+                })
+            })
+    }
+
+    /// Checks whether `reference` in the document `uri` refers to a temporary object created by
+    /// that document
+    ///
+    /// Temporary objects only exist in the session that creates them, and shadow permanent objects
+    /// of the same name.
+    fn references_temporary_object(&self, uri: &str, reference: &str) -> bool {
+        self.definition_map.get(uri).is_some_and(|definitions| {
+            definitions
+                .iter()
+                .any(|d| d.object.temporary && d.object.matches(reference))
+        })
+    }
+
+    /// Returns the relation at the given position and where to insert a policy for it, which is
+    /// below the line the surrounding statement ends on
+    fn policy_insert_position(&self, uri: &str, position: Position) -> Option<(String, Position)> {
+        let parse = self.parse_map.get(uri)?;
+        let rope = self.document_map.get(uri)?;
+        let offset = position_to_offset(position, &rope)?;
+        let table = relation_at_offset(&parse.cst, offset)?;
+        let stmt = parse.stmts.iter().find(|stmt| {
+            usize::from(stmt.range.start()) <= offset && offset <= usize::from(stmt.range.end())
+        })?;
+
+        let end = statement_range(stmt, &rope)?.end;
+        let line_len = rope
+            .get_line(end.line as usize)?
+            .chars()
+            .take_while(|c| *c != '\n' && *c != '\r')
+            .map(char::len_utf16)
+            .sum::<usize>();
+        Some((table, Position::new(end.line, line_len as u32)))
+    }
+
+    /// Returns a symbol for each statement of a document, labelled with the object it creates or
+    /// else with its first line
+    #[allow(deprecated)]
+    fn document_symbols(&self, uri: &str) -> Option<Vec<DocumentSymbol>> {
+        let parse = self.parse_map.get(uri)?;
+        let rope = self.document_map.get(uri)?;
+        let symbols = parse
+            .stmts
+            .iter()
+            .filter_map(|stmt| {
+                let range = statement_range(stmt, &rope)?;
+                let text = rope.get_byte_slice(statement_byte_range(stmt))?.to_string();
+                let (name, kind) = statement_symbol(&stmt.stmt, &text)?;
+                Some(DocumentSymbol {
+                    name,
+                    detail: None,
+                    kind,
+                    tags: None,
+                    deprecated: None,
+                    range,
+                    selection_range: range,
+                    children: None,
+                })
+            })
+            .collect();
+        Some(symbols)
+    }
+
+    /// Returns the edits that format a document, or the part of it within `range`
+    fn formatting_edits(&self, uri: &str, range: Option<Range>) -> Option<Vec<TextEdit>> {
+        let rope = self.document_map.get(uri)?;
+        let bounds = match range {
+            Some(range) => {
+                position_to_offset(range.start, &rope)?..position_to_offset(range.end, &rope)?
+            }
+            None => 0..rope.len_bytes(),
+        };
+        let keyword_case = self.options.read().unwrap().formatting.keyword_case;
+        let edits = keyword_case_edits(&rope.to_string(), keyword_case)
+            .into_iter()
+            .filter(|(edit, _)| bounds.start <= edit.start && edit.end <= bounds.end)
+            .filter_map(|(edit, new_text)| {
+                Some(TextEdit {
+                    range: Range::new(
+                        offset_to_position(edit.start, &rope)?,
+                        offset_to_position(edit.end, &rope)?,
+                    ),
+                    new_text,
+                })
+            })
+            .collect();
+        Some(edits)
+    }
+
+    /// Returns the text of the statement at the given position
+    fn statement_text(&self, params: &TextDocumentPositionParams) -> Option<String> {
+        let uri = params.text_document.uri.to_string();
+        let parse = self.parse_map.get(&uri)?;
+        let rope = self.document_map.get(&uri)?;
+        let offset = position_to_offset(params.position, &rope)?;
+        let stmt = parse.stmts.iter().find(|stmt| {
+            usize::from(stmt.range.start()) <= offset && offset <= usize::from(stmt.range.end())
+        })?;
+        let text = rope.get_byte_slice(statement_byte_range(stmt))?;
+        Some(text.to_string().trim().to_string())
+    }
+
+    /// Returns metadata about the statement at the given position
+    #[tracing::instrument(skip_all, fields(uri = %params.text_document.uri))]
+    async fn statement_info(&self, params: StatementInfoParams) -> Result<Option<StatementInfo>> {
+        let uri = params.text_document.uri.to_string();
+        let (Some(parse), Some(rope)) = (self.parse_map.get(&uri), self.document_map.get(&uri))
+        else {
+            return Ok(None);
+        };
+        let Some(offset) = position_to_offset(params.position, &rope) else {
+            return Ok(None);
+        };
+
+        Ok(parse
+            .stmts
+            .iter()
+            .find(|stmt| {
+                usize::from(stmt.range.start()) <= offset && offset <= usize::from(stmt.range.end())
+            })
+            .and_then(|stmt| StatementInfo::new(stmt, &rope)))
+    }
+}
+
+/// Returns a diagnostic reported by one of the checks on the statements of a document
+///
+/// `code` names the check, so that clients can filter or suppress its diagnostics.
+fn diagnostic(
+    range: Range,
+    severity: DiagnosticSeverity,
+    code: &str,
+    message: impl Into<String>,
+) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        code: Some(NumberOrString::String(code.to_string())),
+        source: Some("postgres_lsp".to_string()),
+        message: message.into(),
+        ..Diagnostic::default()
+    }
+}
+
+/// Returns the objects the statements of a document create
+fn definitions(parse: &Parse, rope: &Rope) -> Vec<Definition> {
+    parse
+        .stmts
+        .iter()
+        .enumerate()
+        .filter_map(|(stmt_idx, stmt)| {
+            Some(Definition {
+                object: DefinedObject::new(&stmt.stmt)?,
+                range: statement_range(stmt, rope)?,
+                stmt_idx,
+            })
+        })
+        .collect()
 }
 
 #[tokio::main]
 async fn main() {
-    env_logger::init();
+    // stdout is used by the protocol, so logs go to stderr or to the file in POSTGRES_LSP_LOG_FILE
+    let (log_filter, log_filter_handle) = reload::Layer::new(
+        EnvFilter::try_from_env("POSTGRES_LSP_LOG").unwrap_or_else(|_| EnvFilter::new("info")),
+    );
+    let log_file = std::env::var_os("POSTGRES_LSP_LOG_FILE")
+        .and_then(|path| open_log_file(Path::new(&path)).ok());
+    let (log_writer, log_writer_handle) = reload::Layer::new(log_layer(match log_file {
+        Some(file) => BoxMakeWriter::new(std::sync::Mutex::new(file)),
+        None => BoxMakeWriter::new(std::io::stderr),
+    }));
+    tracing_subscriber::registry()
+        .with(log_filter)
+        .with(log_writer)
+        .init();
 
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
@@ -301,8 +1187,109 @@ async fn main() {
         document_map: DashMap::new(),
         parse_map: DashMap::new(),
         semantic_token_map: DashMap::new(),
+        definition_map: DashMap::new(),
+        options: RwLock::new(InitializationOptions::default()),
+        log_filter: log_filter_handle,
+        log_writer: log_writer_handle,
     })
+    .custom_method("postgres_lsp/statementInfo", Backend::statement_info)
     .finish();
 
     Server::new(stdin, stdout, socket).serve(service).await;
 }
+
+#[cfg(test)]
+mod tests {
+    use dashmap::DashMap;
+    use parser::{parse_source, SyntaxKind};
+    use ropey::Rope;
+    use serde_json::json;
+    use std::path::Path;
+    use std::sync::RwLock;
+    use tower_lsp::LspService;
+    use tracing_subscriber::fmt::writer::BoxMakeWriter;
+    use tracing_subscriber::{reload, EnvFilter};
+
+    use super::{definitions, log_layer, Backend, InitializationOptions};
+
+    /// Returns a server with the given documents open
+    fn backend(documents: &[(&str, &str)]) -> LspService<Backend> {
+        let (service, _) = LspService::new(|client| Backend {
+            client,
+            document_map: DashMap::new(),
+            parse_map: DashMap::new(),
+            semantic_token_map: DashMap::new(),
+            definition_map: DashMap::new(),
+            options: RwLock::new(InitializationOptions::default()),
+            log_filter: reload::Layer::new(EnvFilter::new("info")).1,
+            log_writer: reload::Layer::new(log_layer(BoxMakeWriter::new(std::io::stderr))).1,
+        });
+        for (uri, text) in documents {
+            let parse = parse_source(text);
+            let rope = Rope::from_str(text);
+            let backend = service.inner();
+            backend
+                .definition_map
+                .insert(uri.to_string(), definitions(&parse, &rope));
+            backend.parse_map.insert(uri.to_string(), parse);
+            backend.document_map.insert(uri.to_string(), rope);
+        }
+        service
+    }
+
+    #[test]
+    fn temporary_objects_are_local() {
+        let session = "file:///session.sql";
+        let schema = "file:///schema.sql";
+        let service = backend(&[
+            (
+                session,
+                "create temporary table users (id int);\nselect * from users;",
+            ),
+            (schema, "create table users (id int);\nselect * from users;"),
+        ]);
+        let backend = service.inner();
+
+        assert!(backend.references_temporary_object(session, "users"));
+        assert!(!backend.references_temporary_object(schema, "users"));
+        assert!(!backend.references_temporary_object(session, "orders"));
+
+        // each document refers to its own table
+        for uri in [session, schema] {
+            let locations = backend.reference_locations(uri, "users", &[SyntaxKind::RangeVar]);
+            assert_eq!(locations.len(), 2, "{}", uri);
+            assert!(
+                locations.iter().all(|(l, _)| l.uri.as_str() == uri),
+                "{}",
+                uri
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_options_do_not_discard_valid_ones() {
+        let options = InitializationOptions::from_value(json!({
+            "lockAnalysis": "yes",
+            "replicatedSchemas": ["replica"],
+            "logLevel": 3,
+            "logFile": "/tmp/postgres_lsp.log",
+        }));
+        assert!(!options.lock_analysis);
+        assert_eq!(options.replicated_schemas, vec!["replica".to_string()]);
+        assert_eq!(options.log_level, None);
+        assert_eq!(
+            options.log_file.as_deref(),
+            Some(Path::new("/tmp/postgres_lsp.log"))
+        );
+        assert_eq!(
+            options.protected_schemas,
+            InitializationOptions::default().protected_schemas
+        );
+    }
+
+    #[test]
+    fn options_that_are_not_an_object_are_ignored() {
+        let options = InitializationOptions::from_value(json!(["lockAnalysis"]));
+        assert!(!options.lock_analysis);
+    }
+}
@@ -0,0 +1,66 @@
+use pg_query::{
+    protobuf::{ObjectType, SelectStmt},
+    NodeEnum,
+};
+
+use crate::typecheck::is_star;
+
+/// Checks whether a `CREATE VIEW` or `CREATE MATERIALIZED VIEW` selects `*`
+///
+/// The columns of such a view are fixed when it is created, and it fetches every column even if
+/// its users only need a few.
+pub fn view_selects_star(stmt: &NodeEnum) -> bool {
+    let query = match stmt {
+        NodeEnum::ViewStmt(s) => s.query.as_ref(),
+        // `CREATE TABLE AS` copies the rows, so only materialized views are affected
+        NodeEnum::CreateTableAsStmt(s) if s.objtype == ObjectType::ObjectMatview as i32 => {
+            s.query.as_ref()
+        }
+        _ => None,
+    };
+    match query.and_then(|q| q.node.as_ref()) {
+        Some(NodeEnum::SelectStmt(select)) => selects_star(select),
+        _ => false,
+    }
+}
+
+fn selects_star(select: &SelectStmt) -> bool {
+    // both sides of a UNION, INTERSECT or EXCEPT make up the columns
+    if let (Some(larg), Some(rarg)) = (&select.larg, &select.rarg) {
+        return selects_star(larg) || selects_star(rarg);
+    }
+    select.target_list.iter().any(is_star)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::view_selects_star;
+    use crate::test_utils::parse;
+
+    fn selects_star(sql: &str) -> bool {
+        view_selects_star(&parse(sql))
+    }
+
+    #[test]
+    fn views_selecting_star() {
+        let cases = [
+            ("create view v as select * from t", true),
+            ("create view v as select t.* from t", true),
+            (
+                "create view v as select id from t union select * from s",
+                true,
+            ),
+            ("create materialized view m as select * from t", true),
+            ("create view v as select id, name from t", false),
+            ("create view v as select count(*) from t", false),
+            ("create materialized view m as select id from t", false),
+            ("create table c as select * from t", false),
+            ("select * into c from t", false),
+            ("select * from t", false),
+        ];
+
+        for (sql, expected) in cases {
+            assert_eq!(selects_star(sql), expected, "{}", sql);
+        }
+    }
+}
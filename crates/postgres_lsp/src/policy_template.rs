@@ -0,0 +1,99 @@
+use pg_query::NodeEnum;
+
+/// Returns the names of the columns a `CREATE TABLE` statement defines
+pub fn table_columns(stmt: &NodeEnum) -> Vec<String> {
+    let NodeEnum::CreateStmt(s) = stmt else {
+        return Vec::new();
+    };
+    s.table_elts
+        .iter()
+        .filter_map(|elt| match &elt.node {
+            Some(NodeEnum::ColumnDef(c)) => Some(c.colname.to_owned()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns a `CREATE POLICY` skeleton for `table`
+///
+/// If the table has one of the `owner_columns`, the policy lets users read their own rows.
+/// Otherwise it denies everything until the condition is filled in.
+pub fn policy_template(table: &str, columns: &[String], owner_columns: &[String]) -> String {
+    let name = format!("{}_select", unquoted_name(table));
+    let condition = owner_columns
+        .iter()
+        .find(|owner| columns.contains(owner))
+        .map_or_else(
+            || "false".to_string(),
+            |owner| format!("auth.uid() = {}", owner),
+        );
+
+    format!(
+        "CREATE POLICY \"{}\" ON {table}\n  FOR SELECT\n  TO authenticated\n  USING ({condition});",
+        name.replace('"', "\"\"")
+    )
+}
+
+/// Returns the last part of a possibly schema-qualified and quoted name without its quotes, e.g.
+/// `My "Table"` for `app."My ""Table"""`
+fn unquoted_name(name: &str) -> String {
+    let mut last = String::new();
+    let mut quoted = false;
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                last.push('"');
+            }
+            '"' => quoted = !quoted,
+            '.' if !quoted => last.clear(),
+            // unquoted names are folded to lower case
+            c if !quoted => last.push(c.to_ascii_lowercase()),
+            c => last.push(c),
+        }
+    }
+    last
+}
+
+#[cfg(test)]
+mod tests {
+    use super::policy_template;
+
+    fn policy_name(table: &str) -> String {
+        let template = policy_template(table, &[], &[]);
+        let name = template.strip_prefix("CREATE POLICY ").unwrap();
+        name[..name.find(" ON ").unwrap()].to_string()
+    }
+
+    #[test]
+    fn policy_names_are_quoted_once() {
+        let cases = [
+            ("orders", r#""orders_select""#),
+            ("app.orders", r#""orders_select""#),
+            ("App.Orders", r#""orders_select""#),
+            (r#""Orders""#, r#""Orders_select""#),
+            (r#"app."Order Items""#, r#""Order Items_select""#),
+            (r#""app.v2"."orders""#, r#""orders_select""#),
+            (
+                r#""my ""quoted"" table""#,
+                r#""my ""quoted"" table_select""#,
+            ),
+        ];
+
+        for (table, expected) in cases {
+            assert_eq!(policy_name(table), expected, "{}", table);
+        }
+    }
+
+    #[test]
+    fn policy_condition_uses_owner_column() {
+        let columns = ["id".to_string(), "user_id".to_string()];
+        let template = policy_template("app.orders", &columns, &["user_id".to_string()]);
+        assert!(template.contains(" ON app.orders\n"));
+        assert!(template.contains("USING (auth.uid() = user_id);"));
+
+        let template = policy_template("app.orders", &columns, &["owner_id".to_string()]);
+        assert!(template.contains("USING (false);"));
+    }
+}
@@ -0,0 +1,353 @@
+use pg_query::{
+    protobuf::{a_const, ObjectType},
+    Node, NodeEnum, NodeRef,
+};
+
+use crate::{
+    definitions::DefinedObject,
+    locks::{foreign_key_targets, statement_locks},
+    statement_info::{qualified_name, range_var_name},
+};
+
+/// Returns the schemas and objects in the `protected` schemas that a statement creates, alters
+/// or drops
+pub fn protected_objects(stmt: &NodeEnum, protected: &[String]) -> Vec<String> {
+    let is_protected = |schema: &str| protected.iter().any(|p| p == schema);
+
+    let mut objects = modified_schemas(stmt)
+        .into_iter()
+        .filter(|schema| is_protected(schema.as_str()))
+        .collect::<Vec<_>>();
+    objects.extend(
+        modified_objects(stmt)
+            .into_iter()
+            .filter(|name| schema_of(name).is_some_and(is_protected)),
+    );
+    objects
+}
+
+/// Returns the relations in the `replicated` schemas that a foreign key or view defined by the
+/// statement depends on, if the statement targets another schema
+pub fn replicated_schema_references(stmt: &NodeEnum, replicated: &[String]) -> Vec<String> {
+    let target = match stmt {
+        NodeEnum::AlterTableStmt(s) => s.relation.as_ref().map(range_var_name),
+        _ => DefinedObject::new(stmt).map(|o| o.name),
+    };
+    let Some(target) = target else {
+        return Vec::new();
+    };
+
+    let referenced = match stmt {
+        NodeEnum::CreateStmt(_) | NodeEnum::AlterTableStmt(_) => foreign_key_targets(stmt)
+            .into_iter()
+            .map(range_var_name)
+            .collect::<Vec<_>>(),
+        NodeEnum::ViewStmt(_) | NodeEnum::CreateTableAsStmt(_) => stmt
+            .nodes()
+            .into_iter()
+            .filter_map(|(node, _, _)| match node {
+                NodeRef::RangeVar(r) => Some(range_var_name(r)),
+                _ => None,
+            })
+            .filter(|name| *name != target)
+            .collect(),
+        _ => return Vec::new(),
+    };
+
+    let mut references = Vec::new();
+    for name in referenced {
+        let Some(schema) = schema_of(&name) else {
+            continue;
+        };
+        if schema_of(&target) != Some(schema)
+            && replicated.iter().any(|r| r == schema)
+            && !references.contains(&name)
+        {
+            references.push(name);
+        }
+    }
+    references
+}
+
+/// Checks whether a `CREATE POLICY` or `ALTER POLICY` reads claims that users can set for
+/// themselves, e.g. `auth.jwt() -> 'user_metadata'`
+pub fn policy_uses_spoofable_claims(stmt: &NodeEnum) -> bool {
+    let (qual, with_check) = match stmt {
+        NodeEnum::CreatePolicyStmt(s) => (&s.qual, &s.with_check),
+        NodeEnum::AlterPolicyStmt(s) => (&s.qual, &s.with_check),
+        _ => return false,
+    };
+
+    [qual, with_check]
+        .into_iter()
+        .filter_map(|expr| expr.as_ref().and_then(|e| e.node.as_ref()))
+        .flat_map(|expr| expr.nodes())
+        .any(|(node, _, _)| match node {
+            // `auth.jwt() -> 'user_metadata'` or `auth.jwt() #>> '{user_metadata,role}'`
+            NodeRef::AExpr(e) => {
+                let is_json_operator = matches!(
+                    qualified_name(&e.name).as_str(),
+                    "->" | "->>" | "#>" | "#>>"
+                );
+                let key = match e.rexpr.as_ref().and_then(|r| r.node.as_ref()) {
+                    Some(NodeEnum::AConst(c)) => match &c.val {
+                        Some(a_const::Val::Sval(s)) => s.sval.as_str(),
+                        _ => "",
+                    },
+                    _ => "",
+                };
+                is_json_operator && key.contains("user_metadata")
+            }
+            // `auth.users.raw_user_meta_data`
+            NodeRef::ColumnRef(c) => qualified_name(&c.fields).ends_with("raw_user_meta_data"),
+            _ => false,
+        })
+}
+
+fn modified_schemas(stmt: &NodeEnum) -> Vec<String> {
+    match stmt {
+        NodeEnum::CreateSchemaStmt(s) => vec![s.schemaname.to_owned()],
+        NodeEnum::RenameStmt(s) if s.rename_type == ObjectType::ObjectSchema as i32 => {
+            vec![s.subname.to_owned()]
+        }
+        NodeEnum::AlterOwnerStmt(s) if s.object_type == ObjectType::ObjectSchema as i32 => s
+            .object
+            .as_deref()
+            .and_then(object_name)
+            .into_iter()
+            .collect(),
+        // moving an object into a schema modifies it as well
+        NodeEnum::AlterObjectSchemaStmt(s) => vec![s.newschema.to_owned()],
+        NodeEnum::DropStmt(s) if s.remove_type == ObjectType::ObjectSchema as i32 => s
+            .objects
+            .iter()
+            .filter_map(|o| match &o.node {
+                Some(NodeEnum::String(schema)) => Some(schema.sval.to_owned()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The names of the objects a statement creates, alters or drops, schema-qualified if they are in
+/// the source
+fn modified_objects(stmt: &NodeEnum) -> Vec<String> {
+    let mut objects = Vec::new();
+
+    if let Some(object) = DefinedObject::new(stmt) {
+        if object.kind != "SCHEMA" {
+            objects.push(object.name);
+        }
+    }
+
+    match stmt {
+        NodeEnum::DropStmt(s) => {
+            for object in s.objects.iter() {
+                match &object.node {
+                    Some(NodeEnum::TypeName(t)) => objects.push(qualified_name(&t.names)),
+                    Some(NodeEnum::ObjectWithArgs(f)) => objects.push(qualified_name(&f.objname)),
+                    // relations are covered by their locks below
+                    _ => {}
+                }
+            }
+        }
+        NodeEnum::AlterFunctionStmt(s) => {
+            objects.extend(s.func.as_ref().map(|f| qualified_name(&f.objname)))
+        }
+        NodeEnum::AlterTypeStmt(s) => objects.push(qualified_name(&s.type_name)),
+        NodeEnum::AlterEnumStmt(s) => objects.push(qualified_name(&s.type_name)),
+        NodeEnum::AlterDomainStmt(s) => objects.push(qualified_name(&s.type_name)),
+        // relations are covered by their locks below, schemas by `modified_schemas`
+        NodeEnum::RenameStmt(s) if s.rename_type != ObjectType::ObjectSchema as i32 => {
+            objects.extend(s.object.as_deref().and_then(object_name))
+        }
+        NodeEnum::AlterOwnerStmt(s) if s.object_type != ObjectType::ObjectSchema as i32 => {
+            objects.extend(s.object.as_deref().and_then(object_name))
+        }
+        NodeEnum::AlterObjectSchemaStmt(s) => objects.extend(
+            s.relation
+                .as_ref()
+                .map(range_var_name)
+                .or_else(|| s.object.as_deref().and_then(object_name)),
+        ),
+        _ => {}
+    }
+
+    let mut locks = statement_locks(stmt);
+    match stmt {
+        // the other locks are taken by foreign keys on the tables they reference
+        NodeEnum::CreateStmt(_) => locks.clear(),
+        NodeEnum::AlterTableStmt(_) => locks.truncate(1),
+        _ => {}
+    }
+    for lock in locks {
+        if !objects.contains(&lock.relation) {
+            objects.push(lock.relation);
+        }
+    }
+
+    objects
+}
+
+/// Returns the name of an object in a `RenameStmt`, `AlterOwnerStmt` or `AlterObjectSchemaStmt`
+fn object_name(object: &Node) -> Option<String> {
+    match object.node.as_ref()? {
+        NodeEnum::List(l) => Some(qualified_name(&l.items)),
+        NodeEnum::ObjectWithArgs(f) => Some(qualified_name(&f.objname)),
+        NodeEnum::TypeName(t) => Some(qualified_name(&t.names)),
+        NodeEnum::String(s) => Some(s.sval.to_owned()),
+        _ => None,
+    }
+}
+
+/// Returns the schema of a qualified name, e.g. `auth` for `auth.users`
+fn schema_of(name: &str) -> Option<&str> {
+    let mut parts = name.rsplit('.');
+    parts.next();
+    parts.next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{policy_uses_spoofable_claims, protected_objects, replicated_schema_references};
+    use crate::test_utils::parse;
+
+    #[test]
+    fn protected_schema_modifications() {
+        let protected = ["auth".to_string(), "storage".to_string()];
+        let cases: [(&str, &[&str]); 23] = [
+            ("create schema auth", &["auth"]),
+            ("drop schema storage cascade", &["storage"]),
+            ("alter schema auth rename to auth2", &["auth"]),
+            ("alter schema auth owner to postgres", &["auth"]),
+            ("create table auth.sessions (id int)", &["auth.sessions"]),
+            ("alter table auth.users add column age int", &["auth.users"]),
+            ("drop table auth.users", &["auth.users"]),
+            ("drop function auth.uid()", &["auth.uid"]),
+            ("drop type auth.factor_type", &["auth.factor_type"]),
+            (
+                "create or replace function auth.uid() returns uuid as 'select null' language sql",
+                &["auth.uid"],
+            ),
+            ("alter function auth.uid() security definer", &["auth.uid"]),
+            ("alter function auth.uid() rename to user_id", &["auth.uid"]),
+            ("alter function auth.uid() owner to postgres", &["auth.uid"]),
+            (
+                "alter type auth.factor_type add value 'phone'",
+                &["auth.factor_type"],
+            ),
+            (
+                "alter type auth.factor_type rename to factor_kind",
+                &["auth.factor_type"],
+            ),
+            (
+                "alter type auth.address add attribute zip text",
+                &["auth.address"],
+            ),
+            ("alter domain auth.email set not null", &["auth.email"]),
+            (
+                "alter table storage.objects set schema public",
+                &["storage.objects"],
+            ),
+            ("alter table public.profiles set schema auth", &["auth"]),
+            ("create index on public.profiles (id)", &[]),
+            ("alter table public.profiles add column age int", &[]),
+            ("alter function public.uid() security definer", &[]),
+            ("alter schema public owner to postgres", &[]),
+        ];
+
+        for (sql, expected) in cases {
+            assert_eq!(
+                protected_objects(&parse(sql), &protected),
+                expected,
+                "{}",
+                sql
+            );
+        }
+    }
+
+    #[test]
+    fn replicated_schema_dependencies() {
+        let replicated = ["shared".to_string()];
+        let cases: [(&str, &[&str]); 9] = [
+            (
+                "create table app.orders (customer_id int references shared.customers (id))",
+                &["shared.customers"],
+            ),
+            (
+                "alter table app.orders add foreign key (customer_id) references shared.customers (id)",
+                &["shared.customers"],
+            ),
+            (
+                "create view app.order_customers as select * from app.orders join shared.customers using (id)",
+                &["shared.customers"],
+            ),
+            (
+                "create materialized view app.customer_names as select name from shared.customers",
+                &["shared.customers"],
+            ),
+            (
+                "create table shared.orders (customer_id int references shared.customers (id))",
+                &[],
+            ),
+            (
+                "create table app.orders (customer_id int references app.customers (id))",
+                &[],
+            ),
+            (
+                "create table orders (customer_id int references customers (id))",
+                &[],
+            ),
+            ("create view app.v as select * from app.orders", &[]),
+            ("select * from shared.customers", &[]),
+        ];
+
+        for (sql, expected) in cases {
+            assert_eq!(
+                replicated_schema_references(&parse(sql), &replicated),
+                expected,
+                "{}",
+                sql
+            );
+        }
+    }
+
+    #[test]
+    fn policies_reading_user_metadata() {
+        let cases = [
+            (
+                "create policy p on t using ((auth.jwt() -> 'user_metadata' ->> 'role') = 'admin')",
+                true,
+            ),
+            (
+                "create policy p on t using ((auth.jwt() #>> '{user_metadata,role}') = 'admin')",
+                true,
+            ),
+            (
+                "alter policy p on t with check ((auth.jwt() -> 'user_metadata' ->> 'org') = org_id::text)",
+                true,
+            ),
+            (
+                "create policy p on t using (exists (select 1 from auth.users u where u.id = auth.uid() and u.raw_user_meta_data ->> 'admin' = 'true'))",
+                true,
+            ),
+            (
+                "create policy p on t using ((auth.jwt() -> 'app_metadata' ->> 'role') = 'admin')",
+                false,
+            ),
+            ("create policy p on t using (auth.uid() = user_id)", false),
+            ("create policy p on t using (note = 'user_metadata')", false),
+            ("select auth.jwt() -> 'user_metadata'", false),
+        ];
+
+        for (sql, expected) in cases {
+            assert_eq!(
+                policy_uses_spoofable_claims(&parse(sql)),
+                expected,
+                "{}",
+                sql
+            );
+        }
+    }
+}
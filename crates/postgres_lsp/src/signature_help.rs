@@ -0,0 +1,195 @@
+use pg_query::{
+    protobuf::{CreateFunctionStmt, FunctionParameterMode, KeywordKind, TypeName},
+    NodeEnum,
+};
+use tower_lsp::lsp_types::{ParameterInformation, ParameterLabel, SignatureInformation};
+
+use crate::statement_info::qualified_name;
+
+/// Returns the name of the function whose argument list surrounds the end of `text`, as it is
+/// written in the source, and the index of the argument the end of `text` is in
+///
+/// This works on tokens rather than the syntax tree since the call is usually incomplete while it
+/// is being typed.
+pub fn call_at_end(text: &str) -> Option<(String, u32)> {
+    // a literal, quoted identifier or comment that is still being typed fails the scan, so leave
+    // it out by cutting the text at the quote or slash it may start at until the scan succeeds
+    let mut end = text.len();
+    let scan = loop {
+        match pg_query::scan(&text[..end]) {
+            Ok(scan) => break scan,
+            Err(_) => end = text[..end].rfind(['\'', '"', '$', '/'])?,
+        }
+    };
+    let tokens = scan
+        .tokens
+        .iter()
+        .map(|t| (&text[t.start as usize..t.end as usize], t.keyword_kind()))
+        .collect::<Vec<_>>();
+
+    // the callee and the number of commas so far of each open parenthesis
+    let mut calls: Vec<(Option<String>, u32)> = Vec::new();
+    for (idx, (token, _)) in tokens.iter().enumerate() {
+        match *token {
+            "(" => calls.push((callee(&tokens[..idx]), 0)),
+            ")" => {
+                calls.pop();
+            }
+            "," => {
+                if let Some((_, commas)) = calls.last_mut() {
+                    *commas += 1;
+                }
+            }
+            ";" => calls.clear(),
+            _ => {}
+        }
+    }
+
+    let (name, commas) = calls.pop()?;
+    Some((name?, commas))
+}
+
+/// Returns the possibly schema-qualified name at the end of `tokens`, if there is one
+///
+/// Reserved keywords are not names, e.g. in `select (1, 2)` or `in (1, 2)`.
+fn callee(tokens: &[(&str, KeywordKind)]) -> Option<String> {
+    let is_name = |(t, kind): &(&str, KeywordKind)| {
+        *kind != KeywordKind::ReservedKeyword
+            && (t.starts_with('"') || t.starts_with(|c: char| c.is_alphabetic()))
+    };
+
+    let (last, rest) = tokens.split_last()?;
+    if !is_name(last) {
+        return None;
+    }
+    match rest {
+        [.., schema, (".", _)] if is_name(schema) => Some(format!("{}.{}", schema.0, last.0)),
+        _ => Some(last.0.to_string()),
+    }
+}
+
+/// Returns the signature of a function created in one of the open documents
+pub fn function_signature(stmt: &CreateFunctionStmt) -> SignatureInformation {
+    let name = qualified_name(&stmt.funcname);
+
+    // output parameters are not passed by the caller
+    let parameters = stmt
+        .parameters
+        .iter()
+        .filter_map(|p| match &p.node {
+            Some(NodeEnum::FunctionParameter(p)) => Some(p),
+            _ => None,
+        })
+        .filter(|p| {
+            !matches!(
+                FunctionParameterMode::from_i32(p.mode),
+                Some(FunctionParameterMode::FuncParamOut | FunctionParameterMode::FuncParamTable)
+            )
+        })
+        .map(|p| {
+            let type_name = p.arg_type.as_ref().map(type_name).unwrap_or_default();
+            let variadic = if p.mode == FunctionParameterMode::FuncParamVariadic as i32 {
+                "VARIADIC "
+            } else {
+                ""
+            };
+            if p.name.is_empty() {
+                format!("{}{}", variadic, type_name)
+            } else {
+                format!("{}{} {}", variadic, p.name, type_name)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    SignatureInformation {
+        label: format!("{}({})", name, parameters.join(", ")),
+        documentation: None,
+        parameters: Some(
+            parameters
+                .into_iter()
+                .map(|label| ParameterInformation {
+                    label: ParameterLabel::Simple(label),
+                    documentation: None,
+                })
+                .collect(),
+        ),
+        active_parameter: None,
+    }
+}
+
+/// Returns a type name as it is usually written, e.g. `text[]` instead of `pg_catalog.text`
+fn type_name(type_name: &TypeName) -> String {
+    let name = qualified_name(&type_name.names);
+    let name = name.strip_prefix("pg_catalog.").unwrap_or(&name);
+    if type_name.array_bounds.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}[]", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pg_query::NodeEnum;
+
+    use super::{call_at_end, function_signature};
+    use crate::test_utils::parse;
+
+    #[test]
+    fn calls_at_end() {
+        let cases = [
+            ("select f(", Some(("f", 0))),
+            ("select f(1, ", Some(("f", 1))),
+            ("select f(1, 2, 3", Some(("f", 2))),
+            ("select app.f(1, ", Some(("app.f", 1))),
+            ("select \"My Func\"(1, ", Some(("\"My Func\"", 1))),
+            ("select app.\"f\"(", Some(("app.\"f\"", 0))),
+            // nested calls
+            ("select f(g(1, 2), ", Some(("f", 1))),
+            ("select f(1, g(2, ", Some(("g", 1))),
+            ("select f(1, (2 + 3) * ", Some(("f", 1))),
+            // commas in literals and quoted identifiers
+            ("select f('a, b', ", Some(("f", 1))),
+            ("select f('(', ", Some(("f", 1))),
+            ("select f(\"a,b\", ", Some(("f", 1))),
+            // unterminated literals, quoted identifiers and comments
+            ("select f('abc", Some(("f", 0))),
+            ("select f(1, 'a, b", Some(("f", 1))),
+            ("select f(1, 'it''s, ", Some(("f", 1))),
+            ("select f(1, $$a, b", Some(("f", 1))),
+            ("select f(1, \"a, b", Some(("f", 1))),
+            ("select f(1, /* a, b", Some(("f", 1))),
+            // not in a call
+            ("select f(1)", None),
+            ("select f(1); select ", None),
+            ("select (1, ", None),
+            ("select * from t where id in (1, ", None),
+            ("select 1", None),
+        ];
+
+        for (text, expected) in cases {
+            assert_eq!(
+                call_at_end(text),
+                expected.map(|(name, idx)| (name.to_string(), idx)),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn signatures() {
+        let sql = "create function app.greet(name text, variadic tags text[], out greeting text) \
+                   returns text as 'select 1' language sql";
+        let NodeEnum::CreateFunctionStmt(stmt) = parse(sql) else {
+            panic!("not a CREATE FUNCTION");
+        };
+
+        let signature = function_signature(&stmt);
+        assert_eq!(
+            signature.label,
+            "app.greet(name text, VARIADIC tags text[])"
+        );
+        assert_eq!(signature.parameters.map(|p| p.len()), Some(2));
+    }
+}
@@ -0,0 +1,284 @@
+use parser::RawStmt;
+use pg_query::{
+    protobuf::{RangeVar, SelectStmt},
+    NodeEnum, NodeRef,
+};
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Position, Range, TextDocumentIdentifier};
+
+use crate::locks::has_option;
+use crate::utils::{statement_byte_range, statement_range};
+
+/// Parameters of the `postgres_lsp/statementInfo` request
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementInfoParams {
+    pub text_document: TextDocumentIdentifier,
+    pub position: Position,
+}
+
+/// Rough classification of a statement, e.g. to decide whether it is safe to run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementKind {
+    Query,
+    Dml,
+    Ddl,
+    Maintenance,
+    Transaction,
+    Other,
+}
+
+impl From<&NodeEnum> for StatementKind {
+    fn from(node: &NodeEnum) -> Self {
+        match node {
+            // `SELECT ... INTO` creates a table
+            NodeEnum::SelectStmt(s) if s.into_clause.is_some() => StatementKind::Ddl,
+            NodeEnum::SelectStmt(s) if modifies_data(s) => StatementKind::Dml,
+            // `EXPLAIN ANALYZE` runs the statement
+            NodeEnum::ExplainStmt(s) if has_option(&s.options, "analyze") => s
+                .query
+                .as_ref()
+                .and_then(|q| q.node.as_ref())
+                .map_or(StatementKind::Query, StatementKind::from),
+            NodeEnum::SelectStmt(_) | NodeEnum::ExplainStmt(_) | NodeEnum::VariableShowStmt(_) => {
+                StatementKind::Query
+            }
+            NodeEnum::InsertStmt(_)
+            | NodeEnum::UpdateStmt(_)
+            | NodeEnum::DeleteStmt(_)
+            | NodeEnum::MergeStmt(_)
+            | NodeEnum::CopyStmt(_)
+            | NodeEnum::CallStmt(_)
+            | NodeEnum::DoStmt(_) => StatementKind::Dml,
+            NodeEnum::VacuumStmt(_)
+            | NodeEnum::ClusterStmt(_)
+            | NodeEnum::ReindexStmt(_)
+            | NodeEnum::CheckPointStmt(_)
+            | NodeEnum::RefreshMatViewStmt(_) => StatementKind::Maintenance,
+            NodeEnum::TransactionStmt(_) => StatementKind::Transaction,
+            NodeEnum::VariableSetStmt(_)
+            | NodeEnum::PrepareStmt(_)
+            | NodeEnum::ExecuteStmt(_)
+            | NodeEnum::DeallocateStmt(_)
+            | NodeEnum::ListenStmt(_)
+            | NodeEnum::UnlistenStmt(_)
+            | NodeEnum::NotifyStmt(_)
+            | NodeEnum::LockStmt(_)
+            | NodeEnum::DiscardStmt(_)
+            | NodeEnum::LoadStmt(_)
+            | NodeEnum::FetchStmt(_)
+            | NodeEnum::ClosePortalStmt(_)
+            | NodeEnum::DeclareCursorStmt(_) => StatementKind::Other,
+            // everything else creates, alters or drops objects
+            _ => StatementKind::Ddl,
+        }
+    }
+}
+
+/// Checks whether the `WITH` clause of a query contains an `INSERT`, `UPDATE` or `DELETE`
+fn modifies_data(select: &SelectStmt) -> bool {
+    let Some(with_clause) = &select.with_clause else {
+        return false;
+    };
+    with_clause.ctes.iter().any(|cte| match &cte.node {
+        Some(NodeEnum::CommonTableExpr(cte)) => matches!(
+            cte.ctequery.as_ref().and_then(|q| q.node.as_ref()),
+            Some(
+                NodeEnum::InsertStmt(_)
+                    | NodeEnum::UpdateStmt(_)
+                    | NodeEnum::DeleteStmt(_)
+                    | NodeEnum::MergeStmt(_)
+            )
+        ),
+        _ => false,
+    })
+}
+
+/// A positional parameter (`$1`, `$2`, ...) used by a statement
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementParameter {
+    pub number: i32,
+    /// The type of the parameter, if it is declared by a `PREPARE` or a cast such as `$1::int`
+    pub type_name: Option<String>,
+}
+
+/// Metadata about a single statement, returned by `postgres_lsp/statementInfo`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementInfo {
+    pub range: Range,
+    pub text: String,
+    pub kind: StatementKind,
+    /// Relations and functions referenced by the statement, schema-qualified if they are in the
+    /// source
+    pub referenced_objects: Vec<String>,
+    pub parameters: Vec<StatementParameter>,
+}
+
+impl StatementInfo {
+    pub fn new(stmt: &RawStmt, rope: &Rope) -> Option<StatementInfo> {
+        let text = rope.get_byte_slice(statement_byte_range(stmt))?.to_string();
+
+        Some(StatementInfo {
+            range: statement_range(stmt, rope)?,
+            text: text.trim().to_string(),
+            kind: StatementKind::from(&stmt.stmt),
+            referenced_objects: referenced_objects(&stmt.stmt),
+            parameters: parameters(&stmt.stmt),
+        })
+    }
+}
+
+/// Returns the name of a relation as it is written in the source, e.g. `public.contact`
+pub fn range_var_name(range_var: &RangeVar) -> String {
+    if range_var.schemaname.is_empty() {
+        range_var.relname.to_owned()
+    } else {
+        format!("{}.{}", range_var.schemaname, range_var.relname)
+    }
+}
+
+/// Joins a list of `String` nodes, e.g. a function name, with dots
+pub fn qualified_name(names: &[pg_query::Node]) -> String {
+    names
+        .iter()
+        .filter_map(|n| match &n.node {
+            Some(NodeEnum::String(s)) => Some(s.sval.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn referenced_objects(stmt: &NodeEnum) -> Vec<String> {
+    let mut objects: Vec<String> = Vec::new();
+    for (node, _, _) in inner_stmt(stmt).nodes() {
+        let name = match node {
+            NodeRef::RangeVar(r) => range_var_name(r),
+            NodeRef::FuncCall(f) => qualified_name(&f.funcname),
+            _ => continue,
+        };
+        if !name.is_empty() && !objects.contains(&name) {
+            objects.push(name);
+        }
+    }
+    objects
+}
+
+fn parameters(stmt: &NodeEnum) -> Vec<StatementParameter> {
+    let mut params: Vec<StatementParameter> = Vec::new();
+
+    if let NodeEnum::PrepareStmt(s) = stmt {
+        for (idx, arg) in s.argtypes.iter().enumerate() {
+            if let Some(NodeEnum::TypeName(t)) = &arg.node {
+                params.push(StatementParameter {
+                    number: idx as i32 + 1,
+                    type_name: Some(qualified_name(&t.names)),
+                });
+            }
+        }
+    }
+
+    for (node, _, _) in inner_stmt(stmt).nodes() {
+        let (number, type_name) = match node {
+            NodeRef::ParamRef(p) => (p.number, None),
+            NodeRef::TypeCast(c) => match (
+                c.arg.as_ref().and_then(|a| a.node.as_ref()),
+                c.type_name.as_ref(),
+            ) {
+                (Some(NodeEnum::ParamRef(p)), Some(t)) => {
+                    (p.number, Some(qualified_name(&t.names)))
+                }
+                _ => continue,
+            },
+            _ => continue,
+        };
+        match params.iter_mut().find(|p| p.number == number) {
+            Some(param) => {
+                if param.type_name.is_none() {
+                    param.type_name = type_name;
+                }
+            }
+            None => params.push(StatementParameter { number, type_name }),
+        }
+    }
+
+    params.sort_by_key(|p| p.number);
+    params
+}
+
+/// `PREPARE` wraps the actual statement, which is not visited by `NodeEnum::nodes`
+fn inner_stmt(stmt: &NodeEnum) -> &NodeEnum {
+    match stmt {
+        NodeEnum::PrepareStmt(s) => s
+            .query
+            .as_ref()
+            .and_then(|q| q.node.as_ref())
+            .unwrap_or(stmt),
+        _ => stmt,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use parser::parse_source;
+    use ropey::Rope;
+    use tower_lsp::lsp_types::{Position, Range};
+
+    use super::{StatementInfo, StatementKind};
+    use crate::test_utils::parse;
+
+    fn kind(sql: &str) -> StatementKind {
+        StatementKind::from(&parse(sql))
+    }
+
+    #[test]
+    fn statement_kinds() {
+        let cases = [
+            ("select 1", StatementKind::Query),
+            ("select * into t2 from t", StatementKind::Ddl),
+            (
+                "with moved as (delete from t returning *) select * from moved",
+                StatementKind::Dml,
+            ),
+            (
+                "with ids as (select id from t) select * from ids",
+                StatementKind::Query,
+            ),
+            ("explain select * from t", StatementKind::Query),
+            ("explain delete from t", StatementKind::Query),
+            ("explain analyze select * from t", StatementKind::Query),
+            ("explain analyze delete from t", StatementKind::Dml),
+            (
+                "explain (analyze, buffers) update t set a = 1",
+                StatementKind::Dml,
+            ),
+            ("insert into t values (1)", StatementKind::Dml),
+            ("vacuum t", StatementKind::Maintenance),
+            ("begin", StatementKind::Transaction),
+            ("create table t (id int)", StatementKind::Ddl),
+            ("set search_path = app", StatementKind::Other),
+        ];
+
+        for (sql, expected) in cases {
+            assert_eq!(kind(sql), expected, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn range_and_text_exclude_surrounding_whitespace() {
+        let text = "select 1;\n\n  select 2;\n";
+        let rope = Rope::from_str(text);
+        let parse = parse_source(text);
+
+        let info = StatementInfo::new(&parse.stmts[1], &rope).unwrap();
+        assert_eq!(info.text, "select 2;");
+        assert_eq!(
+            info.range,
+            Range::new(Position::new(2, 2), Position::new(2, 11))
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use pg_query::{
+    protobuf::{AlterTableType, DefElem, ObjectType},
+    Node, NodeEnum,
+};
+
+use crate::statement_info::qualified_name;
+
+/// Storage parameters of tables, which may also be set for the TOAST table with a `toast.` prefix
+///
+/// See <https://www.postgresql.org/docs/current/sql-createtable.html#SQL-CREATETABLE-STORAGE-PARAMETERS>
+const TABLE_PARAMETERS: &[&str] = &[
+    "autovacuum_enabled",
+    "vacuum_index_cleanup",
+    "vacuum_truncate",
+    "autovacuum_vacuum_threshold",
+    "autovacuum_vacuum_scale_factor",
+    "autovacuum_vacuum_insert_threshold",
+    "autovacuum_vacuum_insert_scale_factor",
+    "autovacuum_vacuum_cost_delay",
+    "autovacuum_vacuum_cost_limit",
+    "autovacuum_freeze_min_age",
+    "autovacuum_freeze_max_age",
+    "autovacuum_freeze_table_age",
+    "autovacuum_multixact_freeze_min_age",
+    "autovacuum_multixact_freeze_max_age",
+    "autovacuum_multixact_freeze_table_age",
+    "log_autovacuum_min_duration",
+];
+
+/// Storage parameters that only apply to the table itself, not to its TOAST table
+const HEAP_ONLY_PARAMETERS: &[&str] = &[
+    "fillfactor",
+    "toast_tuple_target",
+    "parallel_workers",
+    "autovacuum_analyze_threshold",
+    "autovacuum_analyze_scale_factor",
+    "user_catalog_table",
+];
+
+/// Returns the storage parameters set by a `CREATE TABLE`, `CREATE INDEX` or `ALTER TABLE` that
+/// Postgres does not know
+///
+/// Indexes with access methods other than the built-in ones are not checked, since extensions
+/// can define their own parameters.
+pub fn unknown_storage_parameters(stmt: &NodeEnum) -> Vec<String> {
+    match stmt {
+        // `WITH (oids = false)` is still accepted for compatibility, unlike `oids = true`
+        NodeEnum::CreateStmt(s) => unknown_parameters(&s.options, |d| {
+            is_table_parameter(d)
+                || (d.defnamespace.is_empty() && d.defname == "oids" && is_false(d))
+        }),
+        NodeEnum::IndexStmt(s) => {
+            let parameters: &[&str] = match s.access_method.as_str() {
+                "btree" => &["fillfactor", "deduplicate_items"],
+                "hash" | "spgist" => &["fillfactor"],
+                "gist" => &["fillfactor", "buffering"],
+                "gin" => &["fastupdate", "gin_pending_list_limit"],
+                "brin" => &["pages_per_range", "autosummarize"],
+                _ => return Vec::new(),
+            };
+            unknown_parameters(&s.options, |d| {
+                d.defnamespace.is_empty() && parameters.contains(&d.defname.as_str())
+            })
+        }
+        NodeEnum::AlterTableStmt(s) if s.objtype == ObjectType::ObjectTable as i32 => s
+            .cmds
+            .iter()
+            .filter_map(|cmd| match &cmd.node {
+                Some(NodeEnum::AlterTableCmd(cmd)) => Some(cmd),
+                _ => None,
+            })
+            .filter(|cmd| {
+                matches!(
+                    AlterTableType::from_i32(cmd.subtype),
+                    Some(
+                        AlterTableType::AtSetRelOptions
+                            | AlterTableType::AtResetRelOptions
+                            | AlterTableType::AtReplaceRelOptions
+                    )
+                )
+            })
+            .flat_map(|cmd| match cmd.def.as_ref().and_then(|d| d.node.as_ref()) {
+                Some(NodeEnum::List(list)) => unknown_parameters(&list.items, is_table_parameter),
+                _ => Vec::new(),
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn is_table_parameter(parameter: &DefElem) -> bool {
+    let name = parameter.defname.as_str();
+    match parameter.defnamespace.as_str() {
+        "" => TABLE_PARAMETERS.contains(&name) || HEAP_ONLY_PARAMETERS.contains(&name),
+        "toast" => TABLE_PARAMETERS.contains(&name),
+        _ => false,
+    }
+}
+
+/// Checks whether a boolean parameter is set to false, e.g. with `false`, `off` or `0`
+///
+/// A parameter without a value, e.g. `WITH (oids)`, is true.
+fn is_false(parameter: &DefElem) -> bool {
+    let value = match parameter.arg.as_ref().and_then(|a| a.node.as_ref()) {
+        Some(NodeEnum::Boolean(b)) => return !b.boolval,
+        Some(NodeEnum::Integer(i)) => return i.ival == 0,
+        Some(NodeEnum::String(s)) => s.sval.to_owned(),
+        // unquoted words such as `off` are parsed as type names
+        Some(NodeEnum::TypeName(t)) => qualified_name(&t.names),
+        _ => return false,
+    };
+    ["false", "off", "no", "f", "n", "0"]
+        .iter()
+        .any(|v| value.eq_ignore_ascii_case(v))
+}
+
+fn unknown_parameters(options: &[Node], is_known: impl Fn(&DefElem) -> bool) -> Vec<String> {
+    options
+        .iter()
+        .filter_map(|o| match &o.node {
+            Some(NodeEnum::DefElem(d)) if !is_known(d) => {
+                if d.defnamespace.is_empty() {
+                    Some(d.defname.to_owned())
+                } else {
+                    Some(format!("{}.{}", d.defnamespace, d.defname))
+                }
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unknown_storage_parameters;
+    use crate::test_utils::parse;
+
+    fn unknown(sql: &str) -> Vec<String> {
+        unknown_storage_parameters(&parse(sql))
+    }
+
+    #[test]
+    fn unknown_parameters() {
+        let cases: [(&str, &[&str]); 16] = [
+            ("create table t (id int) with (fillfactor = 70)", &[]),
+            (
+                "create table t (id int) with (autovacuum_enabled = false, toast.autovacuum_enabled = false)",
+                &[],
+            ),
+            ("create table t (id int) with (fill_factor = 70)", &["fill_factor"]),
+            ("create table t (id int) with (toast.fillfactor = 70)", &["toast.fillfactor"]),
+            ("create table t (id int) with (oids = false)", &[]),
+            ("create table t (id int) with (oids = off)", &[]),
+            ("create table t (id int) with (oids = 0)", &[]),
+            ("create table t (id int) with (oids = true)", &["oids"]),
+            ("create table t (id int) with (oids)", &["oids"]),
+            ("create table t (id int) without oids", &[]),
+            ("alter table t set (fillfactor = 70)", &[]),
+            ("alter table t reset (autovacuum_enabled, vacuum_truncat)", &["vacuum_truncat"]),
+            ("create index i on t (c) with (fillfactor = 70)", &[]),
+            ("create index i on t using gin (c) with (fillfactor = 70)", &["fillfactor"]),
+            ("create index i on t using brin (c) with (pages_per_range = 32)", &[]),
+            ("create index i on t using ivfflat (c) with (lists = 100)", &[]),
+        ];
+
+        for (sql, expected) in cases {
+            assert_eq!(unknown(sql), expected, "{}", sql);
+        }
+    }
+}
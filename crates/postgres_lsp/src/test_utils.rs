@@ -0,0 +1,11 @@
+use pg_query::NodeEnum;
+
+/// Parses the first statement of `sql` with libpg_query
+pub fn parse(sql: &str) -> NodeEnum {
+    let parsed = pg_query::parse(sql).unwrap();
+    parsed.protobuf.stmts[0]
+        .stmt
+        .as_ref()
+        .and_then(|s| s.node.clone())
+        .unwrap()
+}
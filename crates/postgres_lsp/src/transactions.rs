@@ -0,0 +1,115 @@
+use parser::RawStmt;
+use pg_query::{protobuf::TransactionStmtKind, NodeEnum};
+
+use crate::locks::has_option;
+
+/// Returns the statements of a file that Postgres refuses to run inside an explicit transaction
+/// block, together with a description of the statement
+pub fn statements_outside_transaction(stmts: &[RawStmt]) -> Vec<(&RawStmt, &'static str)> {
+    let mut in_transaction = false;
+    let mut statements = Vec::new();
+
+    for stmt in stmts {
+        match &stmt.stmt {
+            NodeEnum::TransactionStmt(s) => {
+                match TransactionStmtKind::from_i32(s.kind) {
+                    Some(
+                        TransactionStmtKind::TransStmtBegin | TransactionStmtKind::TransStmtStart,
+                    ) => in_transaction = true,
+                    // `COMMIT AND CHAIN` starts a new transaction right away
+                    Some(
+                        TransactionStmtKind::TransStmtCommit
+                        | TransactionStmtKind::TransStmtRollback,
+                    ) => in_transaction = s.chain,
+                    Some(TransactionStmtKind::TransStmtPrepare) => in_transaction = false,
+                    _ => {}
+                }
+            }
+            node if in_transaction => {
+                if let Some(description) = concurrent_index_statement(node) {
+                    statements.push((stmt, description));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    statements
+}
+
+fn concurrent_index_statement(stmt: &NodeEnum) -> Option<&'static str> {
+    match stmt {
+        NodeEnum::IndexStmt(s) if s.concurrent => Some("CREATE INDEX CONCURRENTLY"),
+        NodeEnum::DropStmt(s) if s.concurrent => Some("DROP INDEX CONCURRENTLY"),
+        NodeEnum::ReindexStmt(s) if has_option(&s.params, "concurrently") => {
+            Some("REINDEX CONCURRENTLY")
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use parser::parse_source;
+
+    use super::statements_outside_transaction;
+
+    fn outside_transaction(sql: &str) -> Vec<&'static str> {
+        statements_outside_transaction(&parse_source(sql).stmts)
+            .into_iter()
+            .map(|(_, description)| description)
+            .collect()
+    }
+
+    #[test]
+    fn concurrent_statements_in_transactions() {
+        let cases: [(&str, &[&str]); 12] = [
+            ("create index concurrently i on t (c);", &[]),
+            (
+                "begin;\ncreate index concurrently i on t (c);\ncommit;",
+                &["CREATE INDEX CONCURRENTLY"],
+            ),
+            (
+                "start transaction;\ndrop index concurrently i;\ncommit;",
+                &["DROP INDEX CONCURRENTLY"],
+            ),
+            (
+                "begin;\nreindex index concurrently i;\nrollback;",
+                &["REINDEX CONCURRENTLY"],
+            ),
+            ("begin;\ncreate index i on t (c);\ncommit;", &[]),
+            (
+                "begin;\ncommit;\ncreate index concurrently i on t (c);",
+                &[],
+            ),
+            (
+                "begin;\nrollback;\ncreate index concurrently i on t (c);",
+                &[],
+            ),
+            (
+                "begin;\ncommit and chain;\ncreate index concurrently i on t (c);\ncommit;",
+                &["CREATE INDEX CONCURRENTLY"],
+            ),
+            (
+                "begin;\nsavepoint s;\nrollback to savepoint s;\ncreate index concurrently i on t (c);\ncommit;",
+                &["CREATE INDEX CONCURRENTLY"],
+            ),
+            (
+                "begin;\nsavepoint s;\nrelease savepoint s;\ndrop index concurrently i;\ncommit;",
+                &["DROP INDEX CONCURRENTLY"],
+            ),
+            (
+                "begin;\nprepare transaction 'tx';\ncreate index concurrently i on t (c);",
+                &[],
+            ),
+            (
+                "begin;\ncreate index concurrently i on t (c);\ndrop index concurrently j;\ncommit;",
+                &["CREATE INDEX CONCURRENTLY", "DROP INDEX CONCURRENTLY"],
+            ),
+        ];
+
+        for (sql, expected) in cases {
+            assert_eq!(outside_transaction(sql), expected, "{}", sql);
+        }
+    }
+}
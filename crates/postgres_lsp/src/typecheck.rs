@@ -0,0 +1,123 @@
+use pg_query::{Node, NodeEnum};
+
+/// Checks that an `INSERT` with a column list provides a value for each column, like Postgres
+/// does, and returns the errors
+///
+/// Inserts without a column list are not checked since the columns of the table are unknown.
+pub fn insert_arity_errors(stmt: &NodeEnum) -> Vec<String> {
+    let NodeEnum::InsertStmt(s) = stmt else {
+        return Vec::new();
+    };
+    if s.cols.is_empty() {
+        return Vec::new();
+    }
+    let Some(NodeEnum::SelectStmt(select)) = s.select_stmt.as_ref().and_then(|n| n.node.as_ref())
+    else {
+        return Vec::new();
+    };
+
+    let expressions = if !select.values_lists.is_empty() {
+        // VALUES (...), (...)
+        select
+            .values_lists
+            .iter()
+            .filter_map(|row| match &row.node {
+                Some(NodeEnum::List(list)) => Some(list.items.len()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    } else if !select.target_list.is_empty() && !select.target_list.iter().any(is_star) {
+        vec![select.target_list.len()]
+    } else {
+        // the number of columns of `*` or a set operation is unknown
+        return Vec::new();
+    };
+
+    expressions
+        .into_iter()
+        .filter_map(|count| match count.cmp(&s.cols.len()) {
+            std::cmp::Ordering::Greater => {
+                Some("INSERT has more expressions than target columns".to_string())
+            }
+            std::cmp::Ordering::Less => {
+                Some("INSERT has more target columns than expressions".to_string())
+            }
+            std::cmp::Ordering::Equal => None,
+        })
+        .collect()
+}
+
+/// Checks whether a target is `*` or `t.*`
+pub fn is_star(target: &Node) -> bool {
+    let Some(NodeEnum::ResTarget(t)) = &target.node else {
+        return false;
+    };
+    match t.val.as_ref().and_then(|v| v.node.as_ref()) {
+        Some(NodeEnum::ColumnRef(c)) => c
+            .fields
+            .iter()
+            .any(|f| matches!(f.node, Some(NodeEnum::AStar(_)))),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::insert_arity_errors;
+    use crate::test_utils::parse;
+
+    const MORE_EXPRESSIONS: &str = "INSERT has more expressions than target columns";
+    const MORE_COLUMNS: &str = "INSERT has more target columns than expressions";
+
+    fn errors(sql: &str) -> Vec<String> {
+        insert_arity_errors(&parse(sql))
+    }
+
+    #[test]
+    fn insert_arity() {
+        let cases: [(&str, &[&str]); 14] = [
+            ("insert into t (a, b) values (1, 2)", &[]),
+            ("insert into t (a, b) values (1, 2, 3)", &[MORE_EXPRESSIONS]),
+            ("insert into t (a, b) values (1)", &[MORE_COLUMNS]),
+            (
+                "insert into t (a, b) values (1, 2), (3), (4, 5)",
+                &[MORE_COLUMNS],
+            ),
+            (
+                "insert into t (a, b) values (1, 2, 3), (4)",
+                &[MORE_EXPRESSIONS, MORE_COLUMNS],
+            ),
+            ("insert into t (a, b) select x, y from s", &[]),
+            ("insert into t (a, b) select x from s", &[MORE_COLUMNS]),
+            // a call is a single value however many arguments it takes
+            (
+                "insert into t (a, b) values (concat('a', 'b', 'c', 'd'), 2)",
+                &[],
+            ),
+            (
+                "insert into t (a) values (format('%s %s', variadic array['a', 'b']))",
+                &[],
+            ),
+            // so is a call of an overloaded function, whichever overload is picked
+            (
+                "insert into t (a, b) values (round(1.5), round(1.55, 1))",
+                &[],
+            ),
+            (
+                "insert into t (a, b) values (coalesce(x, y, z))",
+                &[MORE_COLUMNS],
+            ),
+            // the number of columns is unknown
+            ("insert into t values (1, 2, 3)", &[]),
+            ("insert into t (a, b) select * from s", &[]),
+            (
+                "insert into t (a, b) select x from s union select y from u",
+                &[],
+            ),
+        ];
+
+        for (sql, expected) in cases {
+            assert_eq!(errors(sql), expected, "{}", sql);
+        }
+    }
+}
@@ -1,9 +1,109 @@
+use std::ops;
+
+use parser::RawStmt;
 use ropey::Rope;
-use tower_lsp::lsp_types::Position;
+use tower_lsp::lsp_types::{Position, Range};
 
+/// Converts a byte offset into an LSP position, whose character is counted in UTF-16 code units
 pub fn offset_to_position(offset: usize, rope: &Rope) -> Option<Position> {
-    let line = rope.try_char_to_line(offset).ok()?;
+    let char_idx = rope.try_byte_to_char(offset).ok()?;
+    let line = rope.try_char_to_line(char_idx).ok()?;
     let first_char_of_line = rope.try_line_to_char(line).ok()?;
-    let column = offset - first_char_of_line;
+    let column = rope.try_char_to_utf16_cu(char_idx).ok()?
+        - rope.try_char_to_utf16_cu(first_char_of_line).ok()?;
     Some(Position::new(line as u32, column as u32))
 }
+
+/// Converts an LSP position into a byte offset
+///
+/// A character past the end of the line refers to the end of the line, as the LSP specification
+/// requires.
+pub fn position_to_offset(position: Position, rope: &Rope) -> Option<usize> {
+    let first_char_of_line = rope.try_line_to_char(position.line as usize).ok()?;
+    let first_cu_of_line = rope.try_char_to_utf16_cu(first_char_of_line).ok()?;
+    let line_len = rope.get_line(position.line as usize).map_or(0, |line| {
+        line.chars()
+            .take_while(|c| *c != '\n' && *c != '\r')
+            .map(char::len_utf16)
+            .sum()
+    });
+    let char_idx = rope
+        .try_utf16_cu_to_char(first_cu_of_line + line_len.min(position.character as usize))
+        .ok()?;
+    rope.try_char_to_byte(char_idx).ok()
+}
+
+/// Returns the byte range of a statement
+pub fn statement_byte_range(stmt: &RawStmt) -> ops::Range<usize> {
+    stmt.range.start().into()..stmt.range.end().into()
+}
+
+/// Returns the range of a statement
+pub fn statement_range(stmt: &RawStmt, rope: &Rope) -> Option<Range> {
+    Some(Range::new(
+        offset_to_position(stmt.range.start().into(), rope)?,
+        offset_to_position(stmt.range.end().into(), rope)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use parser::parse_source;
+    use proptest::prelude::*;
+    use ropey::Rope;
+    use tower_lsp::lsp_types::{Position, Range};
+
+    use super::{offset_to_position, position_to_offset, statement_range};
+
+    /// Computes the position of `offset` by walking the text
+    fn expected_position(text: &str, offset: usize) -> Position {
+        let before = &text[..offset];
+        let line = before.matches('\n').count();
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+        let column = before[line_start..].encode_utf16().count();
+        Position::new(line as u32, column as u32)
+    }
+
+    proptest! {
+        #[test]
+        fn offsets_round_trip(text in "[a-z;\n é€😀]{0,64}") {
+            let rope = Rope::from_str(&text);
+            for (offset, _) in text.char_indices().chain([(text.len(), ' ')]) {
+                let position = offset_to_position(offset, &rope).unwrap();
+                prop_assert_eq!(position, expected_position(&text, offset));
+                prop_assert_eq!(position_to_offset(position, &rope), Some(offset));
+            }
+        }
+    }
+
+    #[test]
+    fn positions_past_the_end_of_a_line_are_clamped() {
+        let rope = Rope::from_str("select 1;\r\nselect 'é';\nselect 3;");
+        assert_eq!(position_to_offset(Position::new(0, 100), &rope), Some(9));
+        assert_eq!(position_to_offset(Position::new(1, 100), &rope), Some(23));
+        assert_eq!(position_to_offset(Position::new(2, 100), &rope), Some(33));
+    }
+
+    #[test]
+    fn statement_ranges_do_not_split_characters() {
+        for (text, end) in [("select café", 11), ("select 1; select café", 21)] {
+            let rope = Rope::from_str(text);
+            let parse = parse_source(text);
+            let stmt = parse.stmts.last().unwrap();
+            assert_eq!(
+                statement_range(stmt, &rope).map(|r| r.end),
+                Some(Position::new(0, end)),
+                "{}",
+                text
+            );
+        }
+
+        let text = "\nselect café;\n";
+        let rope = Rope::from_str(text);
+        let parse = parse_source(text);
+        assert_eq!(
+            statement_range(&parse.stmts[0], &rope),
+            Some(Range::new(Position::new(1, 0), Position::new(1, 12)))
+        );
+    }
+}
@@ -0,0 +1,33 @@
+use sqlx::PgPool;
+
+use crate::schema_cache::SchemaCacheItem;
+
+#[derive(Debug, Clone, Default)]
+pub struct Extension {
+    name: String,
+    /// The schema the objects of the extension are installed in
+    schema: String,
+    version: String,
+    comment: Option<String>,
+}
+
+impl SchemaCacheItem for Extension {
+    type Item = Extension;
+
+    async fn load(pool: &PgPool) -> Vec<Extension> {
+        sqlx::query_as!(
+            Extension,
+            r#"SELECT
+  e.extname AS name,
+  n.nspname AS schema,
+  e.extversion AS version,
+  obj_description(e.oid, 'pg_extension') AS comment
+FROM
+  pg_extension e
+  JOIN pg_namespace n ON n.oid = e.extnamespace"#
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+}
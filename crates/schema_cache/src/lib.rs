@@ -3,9 +3,16 @@
 #![allow(dead_code)]
 #![feature(future_join)]
 
+mod extensions;
+mod materialized_views;
+mod roles;
 mod schema_cache;
 mod schemas;
+mod sequences;
+mod settings;
 mod tables;
+mod types;
+mod views;
 
 use sqlx::postgres::PgPool;
 
@@ -0,0 +1,45 @@
+use sqlx::PgPool;
+
+use crate::schema_cache::SchemaCacheItem;
+
+#[derive(Debug, Clone, Default)]
+pub struct MaterializedView {
+    id: i64,
+    schema: String,
+    name: String,
+    is_populated: bool,
+    bytes: i64,
+    size: String,
+    comment: Option<String>,
+}
+
+impl SchemaCacheItem for MaterializedView {
+    type Item = MaterializedView;
+
+    async fn load(pool: &PgPool) -> Vec<MaterializedView> {
+        sqlx::query_as!(
+            MaterializedView,
+            r#"SELECT
+  c.oid :: int8 AS "id!",
+  n.nspname AS schema,
+  c.relname AS name,
+  c.relispopulated AS is_populated,
+  pg_total_relation_size(c.oid) :: int8 AS "bytes!",
+  pg_size_pretty(pg_total_relation_size(c.oid)) AS "size!",
+  obj_description(c.oid) AS comment
+FROM
+  pg_class c
+  JOIN pg_namespace n ON n.oid = c.relnamespace
+WHERE
+  c.relkind = 'm'
+  AND (
+    pg_has_role(c.relowner, 'USAGE')
+    OR has_table_privilege(c.oid, 'SELECT')
+    OR has_any_column_privilege(c.oid, 'SELECT')
+  )"#
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+}
@@ -0,0 +1,52 @@
+use sqlx::PgPool;
+
+use crate::schema_cache::SchemaCacheItem;
+
+#[derive(Debug, Clone, Default)]
+pub struct Role {
+    id: i64,
+    name: String,
+    is_superuser: bool,
+    can_login: bool,
+    can_create_db: bool,
+    can_create_role: bool,
+    can_bypass_rls: bool,
+    /// The roles this role is a member of
+    member_of: Vec<String>,
+    comment: Option<String>,
+}
+
+impl SchemaCacheItem for Role {
+    type Item = Role;
+
+    async fn load(pool: &PgPool) -> Vec<Role> {
+        sqlx::query_as!(
+            Role,
+            r#"SELECT
+  r.oid :: int8 AS "id!",
+  r.rolname AS "name!",
+  r.rolsuper AS "is_superuser!",
+  r.rolcanlogin AS "can_login!",
+  r.rolcreatedb AS "can_create_db!",
+  r.rolcreaterole AS "can_create_role!",
+  r.rolbypassrls AS "can_bypass_rls!",
+  ARRAY(
+    SELECT
+      g.rolname :: text
+    FROM
+      pg_auth_members m
+      JOIN pg_roles g ON g.oid = m.roleid
+    WHERE
+      m.member = r.oid
+    ORDER BY
+      g.rolname
+  ) AS "member_of!",
+  shobj_description(r.oid, 'pg_authid') AS comment
+FROM
+  pg_roles r"#
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+}
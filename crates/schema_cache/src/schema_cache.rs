@@ -2,20 +2,74 @@ use std::future::join;
 
 use sqlx::postgres::PgPool;
 
+use crate::extensions::Extension;
+use crate::materialized_views::MaterializedView;
+use crate::roles::Role;
 use crate::schemas::Schema;
+use crate::sequences::Sequence;
+use crate::settings::Setting;
 use crate::tables::Table;
+use crate::types::{DomainConstraint, Type};
+use crate::views::View;
 
 #[derive(Debug, Clone, Default)]
 pub struct SchemaCache {
     pub schemas: Vec<Schema>,
     pub tables: Vec<Table>,
+    pub materialized_views: Vec<MaterializedView>,
+    pub sequences: Vec<Sequence>,
+    pub views: Vec<View>,
+    pub extensions: Vec<Extension>,
+    pub settings: Vec<Setting>,
+    pub roles: Vec<Role>,
+    pub types: Vec<Type>,
+    pub domain_constraints: Vec<DomainConstraint>,
 }
 
 impl SchemaCache {
     pub async fn load(pool: &PgPool) -> SchemaCache {
-        let (schemas, tables) = join!(Schema::load(pool), Table::load(pool)).await;
+        let (
+            schemas,
+            tables,
+            materialized_views,
+            sequences,
+            views,
+            extensions,
+            settings,
+            roles,
+            types,
+            domain_constraints,
+        ) = join!(
+            Schema::load(pool),
+            Table::load(pool),
+            MaterializedView::load(pool),
+            Sequence::load(pool),
+            View::load(pool),
+            Extension::load(pool),
+            Setting::load(pool),
+            Role::load(pool),
+            Type::load(pool),
+            DomainConstraint::load(pool)
+        )
+        .await;
 
-        SchemaCache { schemas, tables }
+        SchemaCache {
+            schemas,
+            tables,
+            materialized_views,
+            sequences,
+            views,
+            extensions,
+            settings,
+            roles,
+            types,
+            domain_constraints,
+        }
+    }
+
+    /// Checks whether the extension `name`, e.g. `pg_trgm`, is installed
+    pub fn has_extension(&self, name: &str) -> bool {
+        self.extensions.iter().any(|e| e.name == name)
     }
 
     /// Applies an AST node to the repository
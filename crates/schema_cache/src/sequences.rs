@@ -0,0 +1,61 @@
+use sqlx::PgPool;
+
+use crate::schema_cache::SchemaCacheItem;
+
+#[derive(Debug, Clone, Default)]
+pub struct Sequence {
+    id: i64,
+    schema: String,
+    name: String,
+    data_type: String,
+    start_value: i64,
+    min_value: i64,
+    max_value: i64,
+    increment: i64,
+    cycle: bool,
+    /// The column the sequence is owned by, e.g. through `serial` or an identity column
+    owned_by_schema: Option<String>,
+    owned_by_table: Option<String>,
+    owned_by_column: Option<String>,
+}
+
+impl SchemaCacheItem for Sequence {
+    type Item = Sequence;
+
+    async fn load(pool: &PgPool) -> Vec<Sequence> {
+        sqlx::query_as!(
+            Sequence,
+            r#"SELECT
+  c.oid :: int8 AS "id!",
+  n.nspname AS schema,
+  c.relname AS name,
+  format_type(s.seqtypid, NULL) AS "data_type!",
+  s.seqstart AS start_value,
+  s.seqmin AS min_value,
+  s.seqmax AS max_value,
+  s.seqincrement AS increment,
+  s.seqcycle AS cycle,
+  owner_n.nspname AS "owned_by_schema?",
+  owner_c.relname AS "owned_by_table?",
+  a.attname AS "owned_by_column?"
+FROM
+  pg_sequence s
+  JOIN pg_class c ON c.oid = s.seqrelid
+  JOIN pg_namespace n ON n.oid = c.relnamespace
+  LEFT JOIN pg_depend d ON d.objid = c.oid
+  AND d.classid = 'pg_class' :: regclass
+  AND d.refclassid = 'pg_class' :: regclass
+  AND d.deptype IN ('a', 'i')
+  LEFT JOIN pg_class owner_c ON owner_c.oid = d.refobjid
+  LEFT JOIN pg_namespace owner_n ON owner_n.oid = owner_c.relnamespace
+  LEFT JOIN pg_attribute a ON a.attrelid = d.refobjid
+  AND a.attnum = d.refobjsubid
+WHERE
+  pg_has_role(c.relowner, 'USAGE')
+  OR has_sequence_privilege(c.oid, 'SELECT, USAGE, UPDATE')"#
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+}
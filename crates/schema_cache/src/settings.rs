@@ -0,0 +1,47 @@
+use sqlx::PgPool;
+
+use crate::schema_cache::SchemaCacheItem;
+
+/// A configuration parameter, as shown by `pg_settings`
+#[derive(Debug, Clone, Default)]
+pub struct Setting {
+    name: String,
+    /// The current value
+    setting: String,
+    unit: Option<String>,
+    category: String,
+    short_desc: String,
+    /// When the parameter can be changed, e.g. `user` for `SET` or `postmaster` for a restart
+    context: String,
+    vartype: String,
+    /// The allowed values of `enum` parameters
+    enumvals: Option<Vec<String>>,
+    min_val: Option<String>,
+    max_val: Option<String>,
+}
+
+impl SchemaCacheItem for Setting {
+    type Item = Setting;
+
+    async fn load(pool: &PgPool) -> Vec<Setting> {
+        sqlx::query_as!(
+            Setting,
+            r#"SELECT
+  name AS "name!",
+  setting AS "setting!",
+  unit,
+  category AS "category!",
+  short_desc AS "short_desc!",
+  context AS "context!",
+  vartype AS "vartype!",
+  enumvals,
+  min_val,
+  max_val
+FROM
+  pg_catalog.pg_settings"#
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+}
@@ -0,0 +1,85 @@
+use sqlx::PgPool;
+
+use crate::schema_cache::SchemaCacheItem;
+
+#[derive(Debug, Clone, Default)]
+pub struct Type {
+    id: i64,
+    schema: String,
+    name: String,
+    /// The labels of an enum type, in their sort order
+    enums: Vec<String>,
+    comment: Option<String>,
+}
+
+impl SchemaCacheItem for Type {
+    type Item = Type;
+
+    async fn load(pool: &PgPool) -> Vec<Type> {
+        sqlx::query_as!(
+            Type,
+            r#"SELECT
+  t.oid :: int8 AS "id!",
+  n.nspname AS "schema!",
+  t.typname AS "name!",
+  ARRAY(
+    SELECT
+      e.enumlabel :: text
+    FROM
+      pg_enum e
+    WHERE
+      e.enumtypid = t.oid
+    ORDER BY
+      e.enumsortorder
+  ) AS "enums!",
+  obj_description(t.oid, 'pg_type') AS comment
+FROM
+  pg_type t
+  JOIN pg_namespace n ON n.oid = t.typnamespace
+WHERE
+  t.typtype IN ('e', 'd')
+  AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+  AND pg_has_role(t.typowner, 'USAGE')"#
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+}
+
+/// A `CHECK` or `NOT NULL` constraint of a domain
+#[derive(Debug, Clone, Default)]
+pub struct DomainConstraint {
+    id: i64,
+    type_id: i64,
+    name: String,
+    definition: String,
+    comment: Option<String>,
+}
+
+impl SchemaCacheItem for DomainConstraint {
+    type Item = DomainConstraint;
+
+    async fn load(pool: &PgPool) -> Vec<DomainConstraint> {
+        sqlx::query_as!(
+            DomainConstraint,
+            r#"SELECT
+  c.oid :: int8 AS "id!",
+  c.contypid :: int8 AS "type_id!",
+  c.conname AS "name!",
+  pg_get_constraintdef(c.oid) AS "definition!",
+  obj_description(c.oid, 'pg_constraint') AS comment
+FROM
+  pg_constraint c
+  JOIN pg_type t ON t.oid = c.contypid
+  JOIN pg_namespace n ON n.oid = t.typnamespace
+WHERE
+  c.contypid <> 0
+  AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+  AND pg_has_role(t.typowner, 'USAGE')"#
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+}
@@ -0,0 +1,61 @@
+use sqlx::PgPool;
+
+use crate::schema_cache::SchemaCacheItem;
+
+#[derive(Debug, Clone, Default)]
+pub struct View {
+    id: i64,
+    schema: String,
+    name: String,
+    definition: String,
+    /// The names of the columns, in order
+    columns: Vec<String>,
+    is_updatable: bool,
+    comment: Option<String>,
+}
+
+impl SchemaCacheItem for View {
+    type Item = View;
+
+    async fn load(pool: &PgPool) -> Vec<View> {
+        sqlx::query_as!(
+            View,
+            r#"SELECT
+  c.oid :: int8 AS "id!",
+  n.nspname AS schema,
+  c.relname AS name,
+  pg_get_viewdef(c.oid) AS "definition!",
+  array_remove(
+    array_agg(a.attname :: text ORDER BY a.attnum),
+    NULL
+  ) AS "columns!",
+  -- see `information_schema.views.is_updatable`
+  (pg_relation_is_updatable(c.oid, false) & 20) = 20 AS "is_updatable!",
+  obj_description(c.oid) AS comment
+FROM
+  pg_class c
+  JOIN pg_namespace n ON n.oid = c.relnamespace
+  LEFT JOIN pg_attribute a ON a.attrelid = c.oid
+  AND a.attnum > 0
+  AND NOT a.attisdropped
+WHERE
+  c.relkind = 'v'
+  AND NOT pg_is_other_temp_schema(n.oid)
+  AND (
+    pg_has_role(c.relowner, 'USAGE')
+    OR has_table_privilege(
+      c.oid,
+      'SELECT, INSERT, UPDATE, DELETE, TRUNCATE, REFERENCES, TRIGGER'
+    )
+    OR has_any_column_privilege(c.oid, 'SELECT, INSERT, UPDATE, REFERENCES')
+  )
+GROUP BY
+  c.oid,
+  c.relname,
+  n.nspname"#
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+}
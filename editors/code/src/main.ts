@@ -1,4 +1,4 @@
-import { ExtensionContext } from 'vscode';
+import { ExtensionContext, workspace } from 'vscode';
 
 import {
     Executable,
@@ -9,6 +9,32 @@ import {
 
 let client: LanguageClient;
 
+// The options the server reads from the `initialize` request. They are only read on startup, so
+// changing a setting takes effect once the server is restarted.
+function initializationOptions() {
+    const config = workspace.getConfiguration('postgres_lsp');
+    return {
+        logLevel: config.get<string | null>('logLevel'),
+        logFile: config.get<string | null>('logFile'),
+        lockAnalysis: config.get<boolean>('lockAnalysis'),
+        protectedSchemas: config.get<string[]>('protectedSchemas'),
+        replicatedSchemas: config.get<string[]>('replicatedSchemas'),
+        policyOwnerColumns: config.get<string[]>('policyOwnerColumns'),
+        database: {
+            targetVersion: config.get<number | null>('database.targetVersion'),
+        },
+        foreignKeys: {
+            requireOnDelete: config.get<boolean>('foreignKeys.requireOnDelete'),
+            disallowedOnDeleteActions: config.get<string[]>(
+                'foreignKeys.disallowedOnDeleteActions'
+            ),
+        },
+        formatting: {
+            keywordCase: config.get<string>('formatting.keywordCase'),
+        },
+    };
+}
+
 export function activate(context: ExtensionContext) {
     // If the extension is launched in debug mode then the debug server options are used
     // Otherwise the run options are used
@@ -23,7 +49,8 @@ export function activate(context: ExtensionContext) {
     // Options to control the language client
     const clientOptions: LanguageClientOptions = {
         // Register the server for plain text documents
-        documentSelector: [{ scheme: 'file', language: 'sql' }]
+        documentSelector: [{ scheme: 'file', language: 'sql' }],
+        initializationOptions: initializationOptions(),
     };
 
     // Create the language client and start the client.